@@ -0,0 +1,667 @@
+use super::{
+    class_name, dtype_name, fill, fill_list,
+    json::{write_item, write_string},
+    logical_type,
+    mmap::Bytes,
+    object_key, open_path,
+    references::{reference_path, reference_type, StoredReferences, STD_REFERENCE},
+    unique_names, ReadRawBytes,
+};
+use duckdb::{
+    core::{DataChunkHandle, FlatVector, Inserter, ListVector, LogicalTypeHandle, LogicalTypeId},
+    ffi,
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use hdf5::types::{EnumType, TypeDescriptor};
+use hdf5_sys::h5r::H5R_type_t;
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt::Write,
+    iter::Peekable,
+    str::Chars,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+pub(super) enum AttributeValue {
+    /// Items of a supported type, read as a list unless the dataspace is scalar.
+    Native {
+        dtype: TypeDescriptor,
+        data: Bytes,
+        scalar: bool,
+    },
+    /// Items resolved to text: the labels of enums and the paths of
+    /// referenced objects, `None` for unknown values and null references.
+    Text {
+        dtype: String,
+        values: Vec<Option<String>>,
+        scalar: bool,
+    },
+    /// JSON fallback for opaque types and other references.
+    Json(String),
+}
+
+/// Copy the stored bytes of `attr` without any conversion.
+fn read_stored(attr: &hdf5::Attribute, file_dtype: &hdf5::Datatype) -> hdf5::Result<Vec<u8>> {
+    let len = attr.size() * file_dtype.size();
+    let mut data = Vec::with_capacity(len);
+    hdf5::h5call!(hdf5_sys::h5a::H5Aread(
+        attr.id(),
+        file_dtype.id(),
+        data.spare_capacity_mut().as_mut_ptr() as *mut _
+    ))?;
+    unsafe {
+        data.set_len(len);
+    }
+    Ok(data)
+}
+
+/// The label of the enum value stored in `item`.
+pub(super) fn enum_label(e: &EnumType, item: &[u8]) -> Option<String> {
+    // HDF5 reports member values in the leading bytes of a `u64`.
+    let mut value = [0u8; 8];
+    value[..item.len()].copy_from_slice(item);
+    let value = u64::from_ne_bytes(value);
+    e.members
+        .iter()
+        .find(|m| m.value == value)
+        .map(|m| m.name.clone())
+}
+
+impl AttributeValue {
+    pub(super) fn read(attr: &hdf5::Attribute) -> hdf5::Result<Self> {
+        let file_dtype = attr.dtype()?;
+        if let Some(ref_type) = reference_type(file_dtype.id()) {
+            let stored = StoredReferences::read_attribute(attr, &file_dtype, ref_type)?;
+            return Ok(Self::Text {
+                dtype: match ref_type {
+                    H5R_type_t::H5R_DATASET_REGION1 => "reference(region)",
+                    STD_REFERENCE => "reference",
+                    _ => "reference(object)",
+                }
+                .to_string(),
+                values: stored
+                    .items()
+                    .map(|r| reference_path(attr.id(), ref_type, r))
+                    .collect(),
+                scalar: attr.is_scalar(),
+            });
+        }
+        match file_dtype.to_descriptor() {
+            Ok(TypeDescriptor::Reference(_)) | Err(_) => {
+                let data = read_stored(attr, &file_dtype)?;
+                let class = hdf5::h5lock!(hdf5_sys::h5t::H5Tget_class(file_dtype.id()));
+                Ok(Self::Json(json_bytes(class, &data)))
+            }
+            Ok(TypeDescriptor::Enum(e)) => {
+                let dtype = TypeDescriptor::Enum(e.clone());
+                let data = attr.read_raw_bytes(&dtype)?;
+                Ok(Self::Text {
+                    dtype: dtype_name(&dtype),
+                    values: data
+                        .chunks_exact(dtype.size())
+                        .map(|item| enum_label(&e, item))
+                        .collect(),
+                    scalar: attr.is_scalar(),
+                })
+            }
+            Ok(dtype) => {
+                let data = Bytes::read(&dtype, attr.read_raw_bytes(&dtype)?);
+                Ok(Self::Native {
+                    dtype,
+                    data,
+                    scalar: attr.is_scalar(),
+                })
+            }
+        }
+    }
+
+    /// The name of the member of the value union, unique per DuckDB type.
+    pub(super) fn member_name(&self) -> String {
+        match self {
+            Self::Native {
+                dtype,
+                scalar: true,
+                ..
+            } => dtype_name(dtype),
+            Self::Native { dtype, .. } => format!("{}[]", dtype_name(dtype)),
+            Self::Text {
+                dtype,
+                scalar: true,
+                ..
+            } => dtype.clone(),
+            Self::Text { dtype, .. } => format!("{dtype}[]"),
+            Self::Json(_) => "json".to_string(),
+        }
+    }
+
+    pub(super) fn logical_type(&self) -> LogicalTypeHandle {
+        match self {
+            Self::Native {
+                dtype,
+                scalar: true,
+                ..
+            } => logical_type(dtype),
+            Self::Native { dtype, .. } => LogicalTypeHandle::list(&logical_type(dtype)),
+            Self::Text { scalar: true, .. } | Self::Json(_) => LogicalTypeId::Varchar.into(),
+            Self::Text { .. } => LogicalTypeHandle::list(&LogicalTypeId::Varchar.into()),
+        }
+    }
+
+    pub(super) fn fill(&self, vector: ffi::duckdb_vector, row: usize) {
+        match self {
+            Self::Native {
+                dtype,
+                data,
+                scalar: true,
+            } => fill(dtype, data, vector, row),
+            Self::Native { dtype, data, .. } => {
+                fill_list(dtype, data, data.len() / dtype.size(), vector, row)
+            }
+            Self::Text {
+                values,
+                scalar: true,
+                ..
+            } => insert_text(&mut FlatVector::from(vector), row, &values[0]),
+            Self::Text { values, .. } => {
+                let mut list = ListVector::from(vector);
+                let offset = list.len();
+                unsafe { ffi::duckdb_list_vector_reserve(vector, (offset + values.len()) as _) };
+                let mut child =
+                    FlatVector::from(unsafe { ffi::duckdb_list_vector_get_child(vector) });
+                for (i, value) in values.iter().enumerate() {
+                    insert_text(&mut child, offset + i, value);
+                }
+                list.set_len(offset + values.len());
+                list.set_entry(row, offset, values.len());
+            }
+            Self::Json(json) => FlatVector::from(vector).insert(row, json.as_str()),
+        }
+    }
+
+    /// Append the value to `json`, as a list unless it is scalar.
+    pub(super) fn write_json(&self, json: &mut String) {
+        match self {
+            Self::Native {
+                dtype,
+                data,
+                scalar,
+            } => write_json_items(
+                json,
+                *scalar,
+                data.chunks_exact(dtype.size()),
+                |json, item| write_item(json, dtype, item),
+            ),
+            Self::Text { values, scalar, .. } => {
+                write_json_items(json, *scalar, values, |json, value| match value {
+                    Some(value) => write_string(json, value),
+                    None => json.push_str("null"),
+                })
+            }
+            Self::Json(value) => json.push_str(value),
+        }
+    }
+}
+
+/// Append `items` to `json`, as a list unless `scalar`.
+fn write_json_items<T>(
+    json: &mut String,
+    scalar: bool,
+    items: impl IntoIterator<Item = T>,
+    write: impl Fn(&mut String, T),
+) {
+    if !scalar {
+        json.push('[');
+    }
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write(json, item);
+    }
+    if !scalar {
+        json.push(']');
+    }
+}
+
+/// Append the attributes of `location` to `json`, as an object by name.
+pub(super) fn write_attributes_json(
+    json: &mut String,
+    location: &hdf5::Location,
+) -> hdf5::Result<()> {
+    json.push('{');
+    for (i, name) in location.attr_names()?.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write_string(json, name);
+        json.push(':');
+        AttributeValue::read(&location.attr(name)?)?.write_json(json);
+    }
+    json.push('}');
+    Ok(())
+}
+
+fn insert_text(vector: &mut FlatVector, row: usize, value: &Option<String>) {
+    match value {
+        Some(value) => vector.insert(row, value.as_str()),
+        None => vector.set_null(row),
+    }
+}
+
+fn json_bytes(class: hdf5_sys::h5t::H5T_class_t, data: &[u8]) -> String {
+    let class = class_name(class);
+    let mut hex = String::with_capacity(data.len() * 2);
+    for b in data {
+        write!(hex, "{b:02x}").unwrap();
+    }
+    format!(r#"{{"class":"{class}","data":"{hex}"}}"#)
+}
+
+/// Parse the next name of option `name` from `chars`, up to one of `ends`.
+/// Names are quoted only when they contain separators.
+fn next_name(
+    name: &str,
+    chars: &mut Peekable<Chars>,
+    ends: &[char],
+) -> Result<String, Box<dyn Error>> {
+    while chars.next_if(|c| *c == ' ').is_some() {}
+    let mut item = String::new();
+    if chars.next_if_eq(&'\'').is_some() {
+        loop {
+            match chars
+                .next()
+                .ok_or_else(|| format!("unterminated quote in {name}"))?
+            {
+                '\\' => item.extend(chars.next()),
+                '\'' => break,
+                c => item.push(c),
+            }
+        }
+        while chars.next_if(|c| *c == ' ').is_some() {}
+    } else {
+        while let Some(c) = chars.next_if(|c| !ends.contains(c)) {
+            item.push(c);
+        }
+        item.truncate(item.trim_end().len());
+    }
+    Ok(item)
+}
+
+/// Parse the names of option `name`, as rendered by DuckDB, e.g.
+/// `[run_id, 'a, b']`.
+pub(super) fn parse_names(name: &str, s: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let items = s
+        .trim()
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .ok_or_else(|| format!("{name} must be a list"))?;
+    let mut names = vec![];
+    let mut chars = items.chars().peekable();
+    loop {
+        let item = next_name(name, &mut chars, &[','])?;
+        match chars.next() {
+            None if item.is_empty() && names.is_empty() => break,
+            None => {
+                names.push(item);
+                break;
+            }
+            Some(',') => names.push(item),
+            Some(_) => return Err(format!("invalid name in {name}").into()),
+        }
+    }
+    Ok(names)
+}
+
+/// Parse the entries of map option `name`, as rendered by DuckDB, e.g.
+/// `{ts=BIGINT, 'a, b'=DOUBLE}`.
+pub(super) fn parse_map(name: &str, s: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let items = s
+        .trim()
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .ok_or_else(|| format!("{name} must be a map"))?;
+    let mut entries = vec![];
+    let mut chars = items.chars().peekable();
+    loop {
+        let key = next_name(name, &mut chars, &['='])?;
+        match chars.next() {
+            None if key.is_empty() && entries.is_empty() => break,
+            Some('=') => {}
+            _ => return Err(format!("invalid entry in {name}").into()),
+        }
+        let value = next_name(name, &mut chars, &[','])?;
+        entries.push((key, value));
+        match chars.next() {
+            None => break,
+            Some(',') => {}
+            Some(_) => return Err(format!("invalid entry in {name}").into()),
+        }
+    }
+    Ok(entries)
+}
+
+/// Read all attributes of a location, in name order.
+fn location_attributes(location: &hdf5::Location) -> hdf5::Result<Vec<(String, AttributeValue)>> {
+    let mut attributes = vec![];
+    for name in location.attr_names()? {
+        let value = AttributeValue::read(&location.attr(&name)?)?;
+        attributes.push((name, value));
+    }
+    Ok(attributes)
+}
+
+/// Read all attributes of an object, in name order.
+fn read_attributes(path: &str, object: &str) -> hdf5::Result<Vec<(String, AttributeValue)>> {
+    let file = open_path(path)?;
+    location_attributes(&file.open_by_token(file.loc_info_by_name(object)?.token)?)
+}
+
+/// Read the attributes of `group`, then of its datasets and its groups,
+/// recursively, with the paths of their objects. Objects already `visited`,
+/// through another hard or soft link, are left out, so that links back to a
+/// parent end.
+fn walk_attributes(
+    group: &hdf5::Group,
+    attributes: &mut Vec<(String, String, AttributeValue)>,
+    visited: &mut HashSet<String>,
+) -> hdf5::Result<()> {
+    let mut push = |location: &hdf5::Location| -> hdf5::Result<()> {
+        for (name, value) in location_attributes(location)? {
+            attributes.push((location.name(), name, value));
+        }
+        Ok(())
+    };
+    push(group)?;
+    for dataset in group.datasets()? {
+        if visited.insert(object_key(&dataset)?) {
+            push(&dataset)?;
+        }
+    }
+    for child in group.groups()? {
+        if visited.insert(object_key(&child)?) {
+            walk_attributes(&child, attributes, visited)?;
+        }
+    }
+    Ok(())
+}
+
+struct Attribute {
+    /// The path of the object, when listing the attributes of many.
+    object: Option<String>,
+    name: String,
+    /// The tag of the member of the value union.
+    member: u8,
+    value: AttributeValue,
+}
+
+pub struct Hdf5AttributesBindData {
+    attributes: Vec<Attribute>,
+    members: usize,
+}
+
+impl Hdf5AttributesBindData {
+    fn new(path: &str, object: &str) -> hdf5::Result<Self> {
+        let attributes = read_attributes(path, object)?;
+        Self::from_attributes(
+            attributes
+                .into_iter()
+                .map(|(name, value)| (None, name, value)),
+        )
+    }
+
+    /// Read the attributes of every object of a file.
+    fn all(path: &str) -> hdf5::Result<Self> {
+        let mut attributes = vec![];
+        let file = open_path(path)?;
+        let mut visited = HashSet::from([object_key(&file)?]);
+        walk_attributes(&file, &mut attributes, &mut visited)?;
+        Self::from_attributes(
+            attributes
+                .into_iter()
+                .map(|(object, name, value)| (Some(object), name, value)),
+        )
+    }
+
+    fn from_attributes(
+        values: impl IntoIterator<Item = (Option<String>, String, AttributeValue)>,
+    ) -> hdf5::Result<Self> {
+        let mut attributes = vec![];
+        let mut members: Vec<String> = vec![];
+        for (object, name, value) in values {
+            let member_name = value.member_name();
+            let member = match members.iter().position(|m| *m == member_name) {
+                Some(member) => member,
+                None => {
+                    members.push(member_name);
+                    members.len() - 1
+                }
+            };
+            // The tag of a DuckDB union is a byte.
+            let member = u8::try_from(member).map_err(|_| {
+                hdf5::Error::from(
+                    "attributes of more than 256 types cannot be read as one UNION".to_string(),
+                )
+            })?;
+            attributes.push(Attribute {
+                object,
+                name,
+                member,
+                value,
+            });
+        }
+        Ok(Self {
+            attributes,
+            members: members.len(),
+        })
+    }
+
+    /// The type of the value column: a union over the distinct attribute types.
+    fn value_type(&self) -> LogicalTypeHandle {
+        let mut names = vec![];
+        let mut types = vec![];
+        for attr in &self.attributes {
+            if usize::from(attr.member) == names.len() {
+                names.push(attr.value.member_name());
+                types.push(attr.value.logical_type());
+            }
+        }
+        if names.is_empty() {
+            return LogicalTypeId::Varchar.into();
+        }
+        let members = names
+            .iter()
+            .map(String::as_str)
+            .zip(types)
+            .collect::<Vec<_>>();
+        LogicalTypeHandle::union_type(members.as_slice())
+    }
+
+    /// Fill the rows from `start`: the object path, name, type and value of
+    /// each attribute of many objects, or the name and value of each of one.
+    fn fill(&self, start: usize, output: &mut DataChunkHandle) {
+        let capacity = unsafe { ffi::duckdb_vector_size() } as usize;
+        let rows = &self.attributes[start.min(self.attributes.len())..];
+        let rows = &rows[..rows.len().min(capacity)];
+        let many = rows.first().is_some_and(|attr| attr.object.is_some());
+        let (names, values) = if many { (1, 3) } else { (0, 1) };
+        let names = output.flat_vector(names);
+        let values = unsafe { ffi::duckdb_data_chunk_get_vector(output.get_ptr(), values) };
+        let member = |idx: usize| unsafe { ffi::duckdb_struct_vector_get_child(values, idx as _) };
+        for (row, attr) in rows.iter().enumerate() {
+            if let Some(object) = &attr.object {
+                output.flat_vector(0).insert(row, object.as_str());
+                output
+                    .flat_vector(2)
+                    .insert(row, attr.value.member_name().as_str());
+            }
+            names.insert(row, attr.name.as_str());
+            let tag = usize::from(attr.member);
+            FlatVector::from(member(0)).as_mut_slice::<u8>()[row] = attr.member;
+            for other in (0..self.members).filter(|m| *m != tag) {
+                FlatVector::from(member(other + 1)).set_null(row);
+            }
+            attr.value.fill(member(tag + 1), row);
+        }
+        output.set_len(rows.len());
+    }
+}
+
+pub struct Hdf5AttributesInitData {
+    index: AtomicUsize,
+}
+
+pub struct Hdf5Attributes;
+
+impl VTab for Hdf5Attributes {
+    type InitData = Hdf5AttributesInitData;
+    type BindData = Hdf5AttributesBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let object = bind.get_parameter(1).to_string();
+        let data = Hdf5AttributesBindData::new(&path, &object)?;
+        bind.add_result_column("name", LogicalTypeId::Varchar.into());
+        bind.add_result_column("value", data.value_type());
+        Ok(data)
+    }
+
+    fn init(_: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Ok(Hdf5AttributesInitData {
+            index: AtomicUsize::new(0),
+        })
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        let capacity = unsafe { ffi::duckdb_vector_size() } as usize;
+        let start = init_data.index.fetch_add(capacity, Ordering::Relaxed);
+        bind_data.fill(start, output);
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+}
+
+/// `read_hdf5_all_attributes(path)`: the attributes of every group and
+/// dataset of a file.
+pub struct Hdf5AllAttributes;
+
+impl VTab for Hdf5AllAttributes {
+    type InitData = Hdf5AttributesInitData;
+    type BindData = Hdf5AttributesBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let data = Hdf5AttributesBindData::all(&path)?;
+        bind.add_result_column("object_path", LogicalTypeId::Varchar.into());
+        bind.add_result_column("attr_name", LogicalTypeId::Varchar.into());
+        bind.add_result_column("dtype", LogicalTypeId::Varchar.into());
+        bind.add_result_column("value", data.value_type());
+        bind.set_cardinality(data.attributes.len() as _, true);
+        Ok(data)
+    }
+
+    fn init(init: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Hdf5Attributes::init(init)
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        let capacity = unsafe { ffi::duckdb_vector_size() } as usize;
+        let start = init_data.index.fetch_add(capacity, Ordering::Relaxed);
+        bind_data.fill(start, output);
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![LogicalTypeId::Varchar.into()])
+    }
+}
+
+/// Column names for attributes, made unique, where empty names become
+/// `attribute`.
+fn column_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    unique_names(
+        names
+            .into_iter()
+            .map(|name| if name.is_empty() { "attribute" } else { name }),
+    )
+}
+
+pub struct Hdf5GroupAttributesBindData {
+    values: Vec<AttributeValue>,
+}
+
+pub struct Hdf5GroupAttributesInitData {
+    done: AtomicBool,
+}
+
+/// `read_hdf5_group_attrs(path, group)`: one row with a column per attribute.
+pub struct Hdf5GroupAttributes;
+
+impl VTab for Hdf5GroupAttributes {
+    type InitData = Hdf5GroupAttributesInitData;
+    type BindData = Hdf5GroupAttributesBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let group = bind.get_parameter(1).to_string();
+        let attributes = read_attributes(&path, &group)?;
+        if attributes.is_empty() {
+            return Err(format!("'{group}' has no attributes").into());
+        }
+        let columns = column_names(attributes.iter().map(|(name, _)| name.as_str()));
+        let mut values = vec![];
+        for (column, (_, value)) in columns.iter().zip(attributes) {
+            bind.add_result_column(column, value.logical_type());
+            values.push(value);
+        }
+        bind.set_cardinality(1, true);
+        Ok(Hdf5GroupAttributesBindData { values })
+    }
+
+    fn init(_: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Ok(Hdf5GroupAttributesInitData {
+            done: AtomicBool::new(false),
+        })
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        if init_data.done.swap(true, Ordering::Relaxed) {
+            output.set_len(0);
+            return Ok(());
+        }
+        for (i, value) in bind_data.values.iter().enumerate() {
+            let vector = unsafe { ffi::duckdb_data_chunk_get_vector(output.get_ptr(), i as _) };
+            value.fill(vector, 0);
+        }
+        output.set_len(1);
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+}
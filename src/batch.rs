@@ -0,0 +1,33 @@
+use hdf5::types::TypeDescriptor;
+use hdf5_sys::h5t::hvl_t;
+
+/// Group `data`, the items of a selection of `shape` in row-major order, into
+/// rows of up to `size` samples along the outer axis. A sample is a single
+/// item of a one-dimensional selection, and a fixed-size array of the inner
+/// axes otherwise. The rows are `hvl_t`s pointing into `data`.
+pub fn batch(
+    dtype: TypeDescriptor,
+    data: &[u8],
+    shape: &[usize],
+    size: usize,
+) -> (TypeDescriptor, Vec<u8>) {
+    let sample = shape.iter().skip(1).rev().fold(dtype, |ty, dim| {
+        TypeDescriptor::FixedArray(Box::new(ty), *dim)
+    });
+    let sample_size = sample.size();
+    let samples = data.len() / sample_size.max(1);
+    let mut rows = Vec::with_capacity(samples.div_ceil(size) * std::mem::size_of::<hvl_t>());
+    for start in (0..samples).step_by(size) {
+        let row = hvl_t {
+            len: size.min(samples - start),
+            p: data[start * sample_size..].as_ptr() as *mut _,
+        };
+        rows.extend_from_slice(unsafe {
+            std::slice::from_raw_parts(
+                (&row as *const hvl_t).cast::<u8>(),
+                std::mem::size_of::<hvl_t>(),
+            )
+        });
+    }
+    (TypeDescriptor::VarLenArray(Box::new(sample)), rows)
+}
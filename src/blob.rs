@@ -0,0 +1,88 @@
+use super::{is_null, Hdf5ReadBindData, Hdf5ReadInitData, Hdf5ReadOptions};
+use duckdb::{
+    core::{DataChunkHandle, LogicalTypeHandle, LogicalTypeId},
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use std::error::Error;
+
+/// Recover the bytes of a BLOB from its VARCHAR rendering, where bytes other
+/// than printable ASCII are escaped as `\xNN`.
+fn unescape_blob(s: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let bytes = s.as_bytes();
+    let mut data = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            let hex = bytes
+                .get(i + 2..i + 4)
+                .filter(|_| bytes.get(i + 1) == Some(&b'x'))
+                .ok_or("invalid escape in BLOB")?;
+            data.push(u8::from_str_radix(std::str::from_utf8(hex)?, 16)?);
+            i += 4;
+        } else {
+            data.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(data)
+}
+
+/// `read_hdf5_blob(blob, dataset)`: `read_hdf5` over an in-memory HDF5 file.
+pub(super) struct Hdf5ReadBlob;
+
+impl VTab for Hdf5ReadBlob {
+    type InitData = Hdf5ReadInitData;
+    type BindData = Hdf5ReadBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let image = unescape_blob(&bind.get_parameter(0).to_string())?;
+        let dataset = Some(bind.get_parameter(1))
+            .filter(|d| !is_null(d))
+            .map(|d| d.to_string());
+        let options = Hdf5ReadOptions::from_bind(bind)?;
+        let data = Hdf5ReadBindData::from_image(&image, dataset.as_deref(), &options)?;
+        for (name, dtype) in data.iter_dtype() {
+            bind.add_result_column(&name, dtype);
+        }
+        bind.set_cardinality(data.len() as _, true);
+        Ok(data)
+    }
+
+    fn init(init: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        let bind_data = unsafe { init.get_bind_data::<Self::BindData>().as_ref() }.unwrap();
+        Ok(bind_data.project(&init.get_column_indices()))
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        bind_data.scan(init_data, output)?;
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Blob.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+
+    fn named_parameters() -> Option<Vec<(String, LogicalTypeHandle)>> {
+        Some(vec![
+            ("endian".to_string(), LogicalTypeId::Varchar.into()),
+            ("apply_scale".to_string(), LogicalTypeId::Boolean.into()),
+            (
+                "max_struct_fields".to_string(),
+                LogicalTypeId::Bigint.into(),
+            ),
+            ("all_double".to_string(), LogicalTypeId::Boolean.into()),
+        ])
+    }
+
+    fn supports_pushdown() -> bool {
+        true
+    }
+}
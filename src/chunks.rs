@@ -0,0 +1,240 @@
+use super::{info::fill_list, open_dataset, open_path, parse_indices};
+use duckdb::{
+    core::{DataChunkHandle, Inserter, LogicalTypeHandle, LogicalTypeId},
+    ffi,
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use hdf5_sys::{h5d, h5p};
+use std::{
+    error::Error,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+/// An allocated chunk of a dataset.
+struct Chunk {
+    /// The coordinates of the first item of the chunk.
+    offset: Vec<i64>,
+    size_bytes: i64,
+    /// A bit per filter of the pipeline, set when the filter was skipped.
+    filter_mask: i64,
+}
+
+pub struct Hdf5ChunksBindData {
+    chunks: Vec<Chunk>,
+}
+
+impl Hdf5ChunksBindData {
+    fn new(path: &str, dataset: &str) -> Result<Self, Box<dyn Error>> {
+        let file = open_path(path)?;
+        let dataset = open_dataset(&file, dataset, path)?;
+        if !dataset.is_chunked() {
+            return Err(format!("'{}' of '{path}' is not chunked", dataset.name()).into());
+        }
+        let space = dataset.space()?;
+        let mut len = 0;
+        hdf5::h5call!(h5d::H5Dget_num_chunks(dataset.id(), space.id(), &mut len))?;
+        let mut chunks = Vec::with_capacity(len as usize);
+        for index in 0..len {
+            let mut offset = vec![0; dataset.ndim()];
+            let (mut filter_mask, mut addr, mut size) = (0, 0, 0);
+            hdf5::h5call!(h5d::H5Dget_chunk_info(
+                dataset.id(),
+                space.id(),
+                index,
+                offset.as_mut_ptr(),
+                &mut filter_mask,
+                &mut addr,
+                &mut size
+            ))?;
+            chunks.push(Chunk {
+                offset: offset.into_iter().map(|o| o as i64).collect(),
+                size_bytes: size as i64,
+                filter_mask: filter_mask as i64,
+            });
+        }
+        Ok(Self { chunks })
+    }
+}
+
+pub struct Hdf5ChunksInitData {
+    /// The index of the next vector to emit.
+    chunk: AtomicUsize,
+}
+
+/// `read_hdf5_chunks(path, dataset)`: the allocated chunks of a chunked
+/// dataset, in the order HDF5 indexes them.
+pub(super) struct Hdf5Chunks;
+
+impl VTab for Hdf5Chunks {
+    type InitData = Hdf5ChunksInitData;
+    type BindData = Hdf5ChunksBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let dataset = bind.get_parameter(1).to_string();
+        let data = Hdf5ChunksBindData::new(&path, &dataset)?;
+        bind.add_result_column(
+            "offset",
+            LogicalTypeHandle::list(&LogicalTypeId::Bigint.into()),
+        );
+        bind.add_result_column("size_bytes", LogicalTypeId::Bigint.into());
+        bind.add_result_column("filter_mask", LogicalTypeId::Bigint.into());
+        bind.set_cardinality(data.chunks.len() as _, true);
+        Ok(data)
+    }
+
+    fn init(_: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Ok(Hdf5ChunksInitData {
+            chunk: AtomicUsize::new(0),
+        })
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let chunks = &func.get_bind_data().chunks;
+        let capacity = unsafe { ffi::duckdb_vector_size() } as usize;
+        let chunk = func.get_init_data().chunk.fetch_add(1, Ordering::Relaxed);
+        let start = (chunk * capacity).min(chunks.len());
+        let count = (chunks.len() - start).min(capacity);
+        let offsets = unsafe { ffi::duckdb_data_chunk_get_vector(output.get_ptr(), 0) };
+        for (row, c) in chunks[start..start + count].iter().enumerate() {
+            fill_list(offsets, row, &c.offset, |v, i, o| {
+                v.as_mut_slice::<i64>()[i] = *o
+            });
+            output.flat_vector(1).as_mut_slice::<i64>()[row] = c.size_bytes;
+            output.flat_vector(2).as_mut_slice::<i64>()[row] = c.filter_mask;
+        }
+        output.set_len(count);
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+}
+
+pub struct Hdf5ChunkBindData {
+    /// The bytes of the chunk as stored, after the filters.
+    data: Vec<u8>,
+    filter_mask: i64,
+}
+
+impl Hdf5ChunkBindData {
+    fn new(path: &str, dataset: &str, offset: &[u64]) -> Result<Self, Box<dyn Error>> {
+        let file = open_path(path)?;
+        let dataset = open_dataset(&file, dataset, path)?;
+        let Some(chunk) = dataset.chunk() else {
+            return Err(format!("'{}' of '{path}' is not chunked", dataset.name()).into());
+        };
+        let shape = dataset.shape();
+        if offset.len() != shape.len() {
+            return Err(format!(
+                "chunk_offset has {} dimensions, but '{}' of '{path}' has {}",
+                offset.len(),
+                dataset.name(),
+                shape.len()
+            )
+            .into());
+        }
+        if offset.iter().zip(&chunk).any(|(o, c)| o % *c as u64 != 0) {
+            return Err(format!(
+                "chunk_offset {offset:?} is not on a chunk boundary of '{}' of '{path}', \
+                 whose chunks are {chunk:?}",
+                dataset.name()
+            )
+            .into());
+        }
+        if offset.iter().zip(&shape).any(|(o, dim)| *o >= *dim as u64) {
+            return Err(format!(
+                "chunk_offset {offset:?} is out of '{}' of '{path}' of shape {shape:?}",
+                dataset.name()
+            )
+            .into());
+        }
+        let (mut filter_mask, mut addr, mut size) = (0, 0, 0);
+        hdf5::h5call!(h5d::H5Dget_chunk_info_by_coord(
+            dataset.id(),
+            offset.as_ptr(),
+            &mut filter_mask,
+            &mut addr,
+            &mut size
+        ))?;
+        if size == 0 {
+            return Err(format!(
+                "no chunk of '{}' of '{path}' is allocated at chunk_offset {offset:?}",
+                dataset.name()
+            )
+            .into());
+        }
+        let mut data = vec![0u8; size as usize];
+        hdf5::h5call!(h5d::H5Dread_chunk(
+            dataset.id(),
+            h5p::H5P_DEFAULT,
+            offset.as_ptr(),
+            &mut filter_mask,
+            data.as_mut_ptr().cast()
+        ))?;
+        Ok(Self {
+            data,
+            filter_mask: filter_mask as i64,
+        })
+    }
+}
+
+pub struct Hdf5ChunkInitData {
+    done: AtomicBool,
+}
+
+/// `read_hdf5_chunk(path, dataset, chunk_offset)`: the bytes of a chunk of
+/// a dataset as stored, without undoing its filters, and its filter mask.
+pub(super) struct Hdf5Chunk;
+
+impl VTab for Hdf5Chunk {
+    type InitData = Hdf5ChunkInitData;
+    type BindData = Hdf5ChunkBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let dataset = bind.get_parameter(1).to_string();
+        let offset = parse_indices("chunk_offset", &bind.get_parameter(2).to_string())?;
+        let data = Hdf5ChunkBindData::new(&path, &dataset, &offset)?;
+        bind.add_result_column("data", LogicalTypeId::Blob.into());
+        bind.add_result_column("filter_mask", LogicalTypeId::Bigint.into());
+        bind.set_cardinality(1, true);
+        Ok(data)
+    }
+
+    fn init(_: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Ok(Hdf5ChunkInitData {
+            done: AtomicBool::new(false),
+        })
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        if func.get_init_data().done.swap(true, Ordering::Relaxed) {
+            output.set_len(0);
+            return Ok(());
+        }
+        let chunk = func.get_bind_data();
+        output.flat_vector(0).insert(0, chunk.data.as_slice());
+        output.flat_vector(1).as_mut_slice::<i64>()[0] = chunk.filter_mask;
+        output.set_len(1);
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeHandle::list(&LogicalTypeId::Bigint.into()),
+        ])
+    }
+}
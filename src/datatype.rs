@@ -0,0 +1,266 @@
+use super::references::reference_type;
+use hdf5::types::{CompoundField, CompoundType, Reference, TypeDescriptor};
+use hdf5_sys::{
+    h5, h5p,
+    h5r::H5R_type_t,
+    h5t::{self, H5T_class_t},
+};
+
+fn class(dtype: &hdf5::Datatype) -> H5T_class_t {
+    hdf5::h5lock!(h5t::H5Tget_class(dtype.id()))
+}
+
+fn super_type(dtype: &hdf5::Datatype) -> hdf5::Result<hdf5::Datatype> {
+    unsafe { hdf5::from_id(hdf5::h5call!(h5t::H5Tget_super(dtype.id()))?) }
+}
+
+fn member_type(dtype: &hdf5::Datatype, index: u32) -> hdf5::Result<hdf5::Datatype> {
+    unsafe { hdf5::from_id(hdf5::h5call!(h5t::H5Tget_member_type(dtype.id(), index))?) }
+}
+
+/// The dimensions of an array datatype, outermost first.
+fn array_dims(dtype: &hdf5::Datatype) -> hdf5::Result<Vec<u64>> {
+    let ndims = hdf5::h5call!(h5t::H5Tget_array_ndims(dtype.id()))?;
+    let mut dims = vec![0; ndims as usize];
+    hdf5::h5call!(h5t::H5Tget_array_dims2(dtype.id(), dims.as_mut_ptr()))?;
+    Ok(dims)
+}
+
+/// Whether `dtype` holds an array datatype of more than one dimension.
+fn has_multi_dim_array(dtype: &hdf5::Datatype) -> hdf5::Result<bool> {
+    match class(dtype) {
+        H5T_class_t::H5T_ARRAY => {
+            Ok(array_dims(dtype)?.len() > 1 || has_multi_dim_array(&super_type(dtype)?)?)
+        }
+        H5T_class_t::H5T_VLEN => has_multi_dim_array(&super_type(dtype)?),
+        H5T_class_t::H5T_COMPOUND => {
+            let members = hdf5::h5call!(h5t::H5Tget_nmembers(dtype.id()))?;
+            for index in 0..members as u32 {
+                if has_multi_dim_array(&member_type(dtype, index)?)? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// The descriptor of `dtype`, with array datatypes of more dimensions as
+/// nested fixed arrays, outermost first: `float[2][3]` is an array of 2
+/// arrays of 3 floats.
+pub fn to_descriptor(dtype: &hdf5::Datatype) -> hdf5::Result<TypeDescriptor> {
+    let mut ty = descriptor(dtype)?;
+    native_enums(dtype, &mut ty)?;
+    Ok(ty)
+}
+
+/// The descriptor of `dtype` as the type of a field of a compound, as
+/// `to_descriptor`.
+pub fn to_field_descriptor(dtype: &hdf5::Datatype) -> hdf5::Result<TypeDescriptor> {
+    let mut ty = field_descriptor(dtype)?;
+    native_enums(dtype, &mut ty)?;
+    Ok(ty)
+}
+
+/// Convert the member values of the enums of `descriptor` from the base
+/// types of `dtype` to native integers, as `H5Dread` converts the items.
+/// HDF5 returns them as stored, whatever the precision, bit offset or byte
+/// order of the base, e.g. `0x80` for `1` of a 1-bit base at bit 7.
+fn native_enums(dtype: &hdf5::Datatype, descriptor: &mut TypeDescriptor) -> hdf5::Result<()> {
+    match descriptor {
+        TypeDescriptor::Enum(e) => {
+            let base = super_type(dtype)?;
+            let native = hdf5::Datatype::from_descriptor(&e.base_type())?;
+            for m in &mut e.members {
+                let mut value = m.value.to_ne_bytes();
+                hdf5::h5call!(h5t::H5Tconvert(
+                    base.id(),
+                    native.id(),
+                    1,
+                    value.as_mut_ptr().cast(),
+                    std::ptr::null_mut(),
+                    h5p::H5P_DEFAULT
+                ))?;
+                // Values are zero-extended, as HDF5 returns them.
+                value[e.size as usize..].fill(0);
+                m.value = u64::from_ne_bytes(value);
+            }
+            Ok(())
+        }
+        TypeDescriptor::Compound(c) => {
+            for f in &mut c.fields {
+                native_enums(&member_type(dtype, f.index as _)?, &mut f.ty)?;
+            }
+            Ok(())
+        }
+        TypeDescriptor::FixedArray(..) if class(dtype) == H5T_class_t::H5T_ARRAY => {
+            // One level of nesting per dimension.
+            let mut base = descriptor;
+            for _ in array_dims(dtype)? {
+                match base {
+                    TypeDescriptor::FixedArray(ty, _) => base = ty,
+                    _ => return Ok(()),
+                }
+            }
+            native_enums(&super_type(dtype)?, base)
+        }
+        TypeDescriptor::VarLenArray(ty) => native_enums(&super_type(dtype)?, ty),
+        _ => Ok(()),
+    }
+}
+
+fn descriptor(dtype: &hdf5::Datatype) -> hdf5::Result<TypeDescriptor> {
+    let error = match dtype.to_descriptor() {
+        Ok(descriptor) => return Ok(descriptor),
+        Err(error) => error,
+    };
+    match class(dtype) {
+        H5T_class_t::H5T_ARRAY => {
+            let base = descriptor(&super_type(dtype)?)?;
+            Ok(array_dims(dtype)?.into_iter().rev().fold(base, |ty, dim| {
+                TypeDescriptor::FixedArray(Box::new(ty), dim as usize)
+            }))
+        }
+        H5T_class_t::H5T_VLEN => Ok(TypeDescriptor::VarLenArray(Box::new(descriptor(
+            &super_type(dtype)?,
+        )?))),
+        H5T_class_t::H5T_COMPOUND => {
+            let members = hdf5::h5call!(h5t::H5Tget_nmembers(dtype.id()))?;
+            let mut fields = vec![];
+            for index in 0..members as u32 {
+                let name = hdf5::h5lock!({
+                    let name = h5t::H5Tget_member_name(dtype.id(), index);
+                    let owned = std::ffi::CStr::from_ptr(name)
+                        .to_string_lossy()
+                        .into_owned();
+                    h5::H5free_memory(name.cast());
+                    owned
+                });
+                let offset = hdf5::h5lock!(h5t::H5Tget_member_offset(dtype.id(), index));
+                let ty = field_descriptor(&member_type(dtype, index)?)?;
+                fields.push(CompoundField::new(&name, ty, offset, index as usize));
+            }
+            Ok(TypeDescriptor::Compound(CompoundType {
+                fields,
+                size: dtype.size(),
+            }))
+        }
+        _ => Err(error),
+    }
+}
+
+/// The descriptor of a field of a compound. Object and region references
+/// are read as the paths they point to, unlike those of a whole dataset.
+fn field_descriptor(dtype: &hdf5::Datatype) -> hdf5::Result<TypeDescriptor> {
+    match reference_type(dtype.id()) {
+        Some(H5R_type_t::H5R_OBJECT1) => Ok(TypeDescriptor::Reference(Reference::Object)),
+        Some(H5R_type_t::H5R_DATASET_REGION1) => Ok(TypeDescriptor::Reference(Reference::Region)),
+        _ => descriptor(dtype),
+    }
+}
+
+/// The datatype of `dtype`, with the fields of compounds at their offsets in
+/// any order. `hdf5::Datatype::from_descriptor` grows a compound field by
+/// field, so it fails when a field comes before one at a larger offset, as
+/// in files whose fields were inserted out of the order of their offsets.
+pub fn from_descriptor(dtype: &TypeDescriptor) -> hdf5::Result<hdf5::Datatype> {
+    match dtype {
+        TypeDescriptor::Compound(c) => {
+            let compound: hdf5::Datatype = unsafe {
+                hdf5::from_id(hdf5::h5call!(h5t::H5Tcreate(
+                    H5T_class_t::H5T_COMPOUND,
+                    c.size
+                ))?)?
+            };
+            for f in &c.fields {
+                let name = std::ffi::CString::new(f.name.as_str()).unwrap();
+                hdf5::h5call!(h5t::H5Tinsert(
+                    compound.id(),
+                    name.as_ptr(),
+                    f.offset,
+                    from_descriptor(&f.ty)?.id()
+                ))?;
+            }
+            Ok(compound)
+        }
+        TypeDescriptor::FixedArray(ty, len) => {
+            let dims = [*len as u64];
+            unsafe {
+                hdf5::from_id(hdf5::h5call!(h5t::H5Tarray_create2(
+                    from_descriptor(ty)?.id(),
+                    1,
+                    dims.as_ptr()
+                ))?)
+            }
+        }
+        TypeDescriptor::VarLenArray(ty) => unsafe {
+            hdf5::from_id(hdf5::h5call!(h5t::H5Tvlen_create(
+                from_descriptor(ty)?.id()
+            ))?)
+        },
+        _ => hdf5::Datatype::from_descriptor(dtype),
+    }
+}
+
+/// The memory datatype to read items of `file_dtype` as `dtype`. HDF5 only
+/// converts between arrays of the same dimensions, so the arrays of more
+/// dimensions of `file_dtype` are kept as they are.
+pub fn memory_type(
+    dtype: &TypeDescriptor,
+    file_dtype: &hdf5::Datatype,
+) -> hdf5::Result<hdf5::Datatype> {
+    if !has_multi_dim_array(file_dtype)? {
+        return from_descriptor(dtype);
+    }
+    match (class(file_dtype), dtype) {
+        (H5T_class_t::H5T_ARRAY, _) => {
+            let dims = array_dims(file_dtype)?;
+            // One level of nesting per dimension.
+            let mut base = dtype;
+            for _ in &dims {
+                match base {
+                    TypeDescriptor::FixedArray(ty, _) => base = ty,
+                    _ => return from_descriptor(dtype),
+                }
+            }
+            let base = memory_type(base, &super_type(file_dtype)?)?;
+            unsafe {
+                hdf5::from_id(hdf5::h5call!(h5t::H5Tarray_create2(
+                    base.id(),
+                    dims.len() as _,
+                    dims.as_ptr()
+                ))?)
+            }
+        }
+        (H5T_class_t::H5T_VLEN, TypeDescriptor::VarLenArray(ty)) => {
+            let base = memory_type(ty, &super_type(file_dtype)?)?;
+            unsafe { hdf5::from_id(hdf5::h5call!(h5t::H5Tvlen_create(base.id()))?) }
+        }
+        (H5T_class_t::H5T_COMPOUND, TypeDescriptor::Compound(c)) => {
+            let compound: hdf5::Datatype = unsafe {
+                hdf5::from_id(hdf5::h5call!(h5t::H5Tcreate(
+                    H5T_class_t::H5T_COMPOUND,
+                    c.size
+                ))?)?
+            };
+            for f in &c.fields {
+                let name = std::ffi::CString::new(f.name.as_str()).unwrap();
+                // Fields are matched by name, as `columns` may select some.
+                let index = hdf5::h5lock!(h5t::H5Tget_member_index(file_dtype.id(), name.as_ptr()));
+                let ty = match u32::try_from(index) {
+                    Ok(index) => memory_type(&f.ty, &member_type(file_dtype, index)?)?,
+                    Err(_) => from_descriptor(&f.ty)?,
+                };
+                hdf5::h5call!(h5t::H5Tinsert(
+                    compound.id(),
+                    name.as_ptr(),
+                    f.offset,
+                    ty.id()
+                ))?;
+            }
+            Ok(compound)
+        }
+        _ => from_descriptor(dtype),
+    }
+}
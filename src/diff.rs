@@ -0,0 +1,195 @@
+use super::{
+    fill, logical_type, open_dataset, open_path, read_f64, vlen_elements, Hdf5FileData,
+    Hdf5ReadOptions,
+};
+use duckdb::{
+    core::{DataChunkHandle, LogicalTypeHandle, LogicalTypeId},
+    ffi,
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use hdf5::types::{TypeDescriptor, VarLenAscii, VarLenUnicode};
+use std::{
+    error::Error,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Whether two items of `dtype` are equal, with numbers within `tolerance`.
+fn items_equal(dtype: &TypeDescriptor, a: &[u8], b: &[u8], tolerance: f64) -> bool {
+    match dtype {
+        TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) if tolerance == 0.0 => {
+            a[..dtype.size()] == b[..dtype.size()]
+        }
+        TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) | TypeDescriptor::Float(_) => {
+            let (a, b) = (read_f64(dtype, a).unwrap(), read_f64(dtype, b).unwrap());
+            (a - b).abs() <= tolerance || (a.is_nan() && b.is_nan())
+        }
+        TypeDescriptor::Enum(e) => items_equal(&e.base_type(), a, b, 0.0),
+        TypeDescriptor::Compound(c) => c
+            .fields
+            .iter()
+            .all(|f| items_equal(&f.ty, &a[f.offset..], &b[f.offset..], tolerance)),
+        TypeDescriptor::FixedArray(ty, len) => (0..*len).all(|i| {
+            let offset = i * ty.size();
+            items_equal(ty, &a[offset..], &b[offset..], tolerance)
+        }),
+        TypeDescriptor::VarLenArray(ty) => {
+            let (a, b) = (vlen_elements(ty, a), vlen_elements(ty, b));
+            a.len() == b.len()
+                && (0..a.len() / ty.size().max(1)).all(|i| {
+                    let offset = i * ty.size();
+                    items_equal(ty, &a[offset..], &b[offset..], tolerance)
+                })
+        }
+        TypeDescriptor::VarLenAscii => {
+            let string = |slice: &[u8]| unsafe { slice.as_ptr().cast::<VarLenAscii>().as_ref() };
+            string(a).map(|s| s.as_bytes()) == string(b).map(|s| s.as_bytes())
+        }
+        TypeDescriptor::VarLenUnicode => {
+            let string = |slice: &[u8]| unsafe { slice.as_ptr().cast::<VarLenUnicode>().as_ref() };
+            string(a).map(|s| s.as_bytes()) == string(b).map(|s| s.as_bytes())
+        }
+        _ => a[..dtype.size()] == b[..dtype.size()],
+    }
+}
+
+pub struct Hdf5DiffBindData {
+    dtype: TypeDescriptor,
+    a: Hdf5FileData,
+    b: Hdf5FileData,
+    /// The indices of the items that differ.
+    indices: Vec<usize>,
+}
+
+impl Hdf5DiffBindData {
+    fn new(
+        a: (&str, &str),
+        b: (&str, &str),
+        tolerance: f64,
+        options: &Hdf5ReadOptions,
+    ) -> Result<Self, Box<dyn Error>> {
+        let read = |(path, dataset): (&str, &str)| -> Result<_, Box<dyn Error>> {
+            let file = open_path(path)?;
+            let shape = open_dataset(&file, dataset, path)?.shape();
+            Ok((
+                shape,
+                Hdf5FileData::read(&file, path, Some(dataset), options, false)?,
+            ))
+        };
+        let (shape_a, data_a) = read(a)?;
+        let (shape_b, data_b) = read(b)?;
+        if shape_a != shape_b {
+            return Err(format!(
+                "shape {shape_a:?} of '{}' does not match {shape_b:?} of '{}'",
+                a.1, b.1
+            )
+            .into());
+        }
+        if data_a.dtype != data_b.dtype {
+            return Err(format!("datatype of '{}' does not match '{}'", a.1, b.1).into());
+        }
+        let dtype = data_a.dtype.clone();
+        let item_size = dtype.size();
+        let indices = data_a
+            .items()?
+            .chunks_exact(item_size)
+            .zip(data_b.items()?.chunks_exact(item_size))
+            .enumerate()
+            .filter(|(_, (a, b))| !items_equal(&dtype, a, b, tolerance))
+            .map(|(i, _)| i)
+            .collect();
+        Ok(Self {
+            dtype,
+            a: data_a,
+            b: data_b,
+            indices,
+        })
+    }
+}
+
+pub struct Hdf5DiffInitData {
+    /// The index of the next vector to emit.
+    chunk: AtomicUsize,
+}
+
+/// `hdf5_diff(path_a, dataset_a, path_b, dataset_b)`: the items of two
+/// datasets of the same shape that differ.
+pub(super) struct Hdf5Diff;
+
+impl VTab for Hdf5Diff {
+    type InitData = Hdf5DiffInitData;
+    type BindData = Hdf5DiffBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path_a = bind.get_parameter(0).to_string();
+        let dataset_a = bind.get_parameter(1).to_string();
+        let path_b = bind.get_parameter(2).to_string();
+        let dataset_b = bind.get_parameter(3).to_string();
+        let tolerance = bind
+            .get_named_parameter("tolerance")
+            .map(|t| t.to_string().parse::<f64>())
+            .transpose()?
+            .unwrap_or_default();
+        if tolerance.is_nan() || tolerance < 0.0 {
+            return Err("tolerance must not be negative".into());
+        }
+        let options = Hdf5ReadOptions::from_bind(bind)?;
+        let data = Hdf5DiffBindData::new(
+            (&path_a, &dataset_a),
+            (&path_b, &dataset_b),
+            tolerance,
+            &options,
+        )?;
+        bind.add_result_column("index", LogicalTypeId::UBigint.into());
+        bind.add_result_column("a", logical_type(&data.dtype));
+        bind.add_result_column("b", logical_type(&data.dtype));
+        bind.set_cardinality(data.indices.len() as _, true);
+        Ok(data)
+    }
+
+    fn init(_: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Ok(Hdf5DiffInitData {
+            chunk: AtomicUsize::new(0),
+        })
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let capacity = unsafe { ffi::duckdb_vector_size() } as usize;
+        let chunk = func.get_init_data().chunk.fetch_add(1, Ordering::Relaxed);
+        let total = bind_data.indices.len();
+        let start = (chunk * capacity).min(total);
+        let count = (total - start).min(capacity);
+        let item_size = bind_data.dtype.size();
+        let column =
+            |idx: usize| unsafe { ffi::duckdb_data_chunk_get_vector(output.get_ptr(), idx as _) };
+        let (a, b) = (column(1), column(2));
+        for row in 0..count {
+            let index = bind_data.indices[start + row];
+            output.flat_vector(0).as_mut_slice::<u64>()[row] = index as u64;
+            let offset = index * item_size;
+            fill(&bind_data.dtype, &bind_data.a.items()?[offset..], a, row);
+            fill(&bind_data.dtype, &bind_data.b.items()?[offset..], b, row);
+        }
+        output.set_len(count);
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+
+    fn named_parameters() -> Option<Vec<(String, LogicalTypeHandle)>> {
+        Some(vec![(
+            "tolerance".to_string(),
+            LogicalTypeId::Double.into(),
+        )])
+    }
+}
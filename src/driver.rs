@@ -0,0 +1,187 @@
+use std::{error::Error, path::Path, str::FromStr};
+
+/// The virtual file driver that opens the files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Driver {
+    /// One file on disk.
+    #[default]
+    Sec2,
+    /// One file on disk, read bypassing the system cache, if HDF5 is built
+    /// with the direct driver.
+    Direct,
+    /// A file split across numbered members of a fixed size.
+    Family,
+    /// A file whose metadata and raw data are stored in two files.
+    Split,
+}
+
+impl FromStr for Driver {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sec2" => Ok(Self::Sec2),
+            "direct" => Ok(Self::Direct),
+            "family" => Ok(Self::Family),
+            "split" => Ok(Self::Split),
+            _ => Err(format!(
+                "unknown driver '{s}', expected 'sec2', 'direct', 'family' or 'split'"
+            )),
+        }
+    }
+}
+
+/// Whether HDF5 is built with the direct driver, which then registers it at
+/// startup.
+pub fn direct_supported() -> bool {
+    unsafe { hdf5_sys::h5fd::H5FDis_driver_registered_by_name(c"direct".as_ptr()) > 0 }
+}
+
+/// Open one file on disk with the direct driver, or the sec2 driver if
+/// `driver` is not direct or HDF5 is built without it, aligning objects to
+/// `alignment` bytes.
+pub fn open_direct(
+    path: &str,
+    driver: Driver,
+    alignment: Option<usize>,
+) -> hdf5::Result<hdf5::File> {
+    use hdf5_sys::{h5f, h5p};
+    let fapl: hdf5::plist::PropertyList = unsafe {
+        hdf5::from_id(hdf5::h5call!(h5p::H5Pcreate(
+            *hdf5::globals::H5P_FILE_ACCESS
+        ))?)?
+    };
+    if let Some(alignment) = alignment {
+        hdf5::h5call!(h5p::H5Pset_alignment(fapl.id(), 1, alignment as u64))?;
+    }
+    if driver == Driver::Direct && direct_supported() {
+        hdf5::h5call!(h5p::H5Pset_driver_by_name(
+            fapl.id(),
+            c"direct".as_ptr(),
+            std::ptr::null()
+        ))?;
+    }
+    let name = std::ffi::CString::new(path).map_err(|_| "path contains a NUL byte")?;
+    let id = hdf5::h5call!(h5f::H5Fopen(name.as_ptr(), h5f::H5F_ACC_RDONLY, fapl.id()))?;
+    unsafe { hdf5::from_id(id) }
+}
+
+/// The pattern of the member names of a family, `printf`-style with one
+/// integer, e.g. `data-%06d.h5`.
+struct MemberPattern<'a> {
+    prefix: &'a str,
+    suffix: &'a str,
+    /// The width of the index, padded with zeros if `zero`.
+    width: usize,
+    zero: bool,
+}
+
+impl<'a> MemberPattern<'a> {
+    fn parse(path: &'a str) -> Result<Self, String> {
+        let invalid = || format!("family path '{path}' must contain one %d for the member index");
+        let start = path.find('%').ok_or_else(invalid)?;
+        let spec = &path[start + 1..];
+        let zero = spec.starts_with('0');
+        let digits = spec
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(spec.len());
+        if !spec[digits..].starts_with('d') {
+            return Err(invalid());
+        }
+        let suffix = &spec[digits + 1..];
+        if suffix.contains('%') {
+            return Err(invalid());
+        }
+        Ok(Self {
+            prefix: &path[..start],
+            suffix,
+            width: spec[..digits].parse().unwrap_or(0),
+            zero,
+        })
+    }
+
+    fn member(&self, index: usize) -> String {
+        let (prefix, suffix, width) = (self.prefix, self.suffix, self.width);
+        if self.zero {
+            format!("{prefix}{index:0width$}{suffix}")
+        } else {
+            format!("{prefix}{index:width$}{suffix}")
+        }
+    }
+
+    /// The directory of the members, and the start of their file names.
+    fn split_dir(&self) -> (&'a str, &'a str) {
+        match self.prefix.rfind('/') {
+            Some(i) => (&self.prefix[..=i], &self.prefix[i + 1..]),
+            None => ("./", self.prefix),
+        }
+    }
+
+    /// The index of a member with the file name `name`, if it follows the
+    /// pattern.
+    fn index(&self, name: &str) -> Option<usize> {
+        let index = name
+            .strip_prefix(self.split_dir().1)?
+            .strip_suffix(self.suffix)?;
+        index.trim_start().parse().ok()
+    }
+}
+
+/// Open a family file, checking that its members have no gaps. Without
+/// `member_size`, HDF5 takes the size of the first member.
+pub fn open_family(path: &str, member_size: Option<usize>) -> Result<hdf5::File, Box<dyn Error>> {
+    let pattern = MemberPattern::parse(path)?;
+    let count = (0..)
+        .take_while(|i| Path::new(&pattern.member(*i)).exists())
+        .count();
+    let missing = pattern.member(count);
+    if count == 0 {
+        return Err(format!("member '{missing}' of family '{path}' is missing").into());
+    }
+    for entry in std::fs::read_dir(pattern.split_dir().0)? {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        if let Some(index) = pattern.index(&name).filter(|i| *i > count) {
+            return Err(format!(
+                "member '{missing}' of family '{path}' is missing, but '{}' exists",
+                pattern.member(index)
+            )
+            .into());
+        }
+    }
+    Ok(hdf5::File::with_options()
+        .with_fapl(|fapl| fapl.family_options(member_size.unwrap_or_default()))
+        .open(path)?)
+}
+
+/// The extensions of the metadata and raw data files of a split file, as
+/// named by common tools and by default in HDF5.
+const SPLIT_EXTENSIONS: [(&str, &str); 2] = [("-m.h5", "-r.h5"), (".meta", ".raw")];
+
+/// Open a split file by its base path, or by the path of either of its files.
+pub fn open_split(path: &str) -> Result<hdf5::File, Box<dyn Error>> {
+    let (base, (meta_ext, raw_ext)) = SPLIT_EXTENSIONS
+        .iter()
+        .find_map(|(meta, raw)| {
+            let base = path.strip_suffix(meta).or_else(|| path.strip_suffix(raw))?;
+            Some((base, (*meta, *raw)))
+        })
+        .or_else(|| {
+            SPLIT_EXTENSIONS
+                .iter()
+                .find(|(meta, raw)| {
+                    Path::new(&format!("{path}{meta}")).exists()
+                        || Path::new(&format!("{path}{raw}")).exists()
+                })
+                .map(|ext| (path, *ext))
+        })
+        .ok_or_else(|| format!("no split file found at '{path}'"))?;
+    for (kind, ext) in [("metadata", meta_ext), ("raw data", raw_ext)] {
+        let file = format!("{base}{ext}");
+        if !Path::new(&file).exists() {
+            return Err(format!("{kind} file '{file}' of split file '{base}' is missing").into());
+        }
+    }
+    Ok(hdf5::File::with_options()
+        .with_fapl(|fapl| fapl.split_options(meta_ext, raw_ext))
+        .open(base)?)
+}
@@ -0,0 +1,302 @@
+use super::{info::fill_list, open_path, ReadRawBytes};
+use duckdb::{
+    core::{DataChunkHandle, Inserter, LogicalTypeHandle, LogicalTypeId},
+    ffi,
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use hdf5::{
+    types::{TypeDescriptor, VarLenAscii, VarLenUnicode},
+    Container,
+};
+use std::{
+    error::Error,
+    ops::Deref,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A `GROUP` or `OBJECT` of ODL, the format of HDF-EOS structure metadata.
+#[derive(Debug, Default)]
+struct Node {
+    name: String,
+    values: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    /// Parse the nodes of `text`, e.g. `GROUP=GRID_1 ... END_GROUP=GRID_1`,
+    /// as the children of a root node.
+    fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+        let mut stack = vec![Self::default()];
+        let mut lines = text
+            .lines()
+            .map(|line| line.trim_matches(|c: char| c.is_whitespace() || c == '\0'));
+        while let Some(line) = lines.next() {
+            if line.is_empty() {
+                continue;
+            }
+            if line == "END" {
+                break;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("invalid StructMetadata line: {line}"))?;
+            let mut value = value.trim().to_string();
+            // Long lists continue on the next lines.
+            while value.starts_with('(') && !value.ends_with(')') {
+                match lines.next() {
+                    Some(line) => value.push_str(line),
+                    None => return Err("unterminated list in StructMetadata".into()),
+                }
+            }
+            match key.trim() {
+                "GROUP" | "OBJECT" => stack.push(Self {
+                    name: value,
+                    ..Self::default()
+                }),
+                "END_GROUP" | "END_OBJECT" => {
+                    let node = stack.pop().filter(|_| !stack.is_empty());
+                    match node {
+                        Some(node) => stack.last_mut().unwrap().children.push(node),
+                        None => return Err(format!("unmatched {line} in StructMetadata").into()),
+                    }
+                }
+                key => stack
+                    .last_mut()
+                    .unwrap()
+                    .values
+                    .push((key.to_string(), value)),
+            }
+        }
+        match stack.pop() {
+            Some(root) if stack.is_empty() => Ok(root),
+            _ => Err("unterminated GROUP or OBJECT in StructMetadata".into()),
+        }
+    }
+
+    /// The value of the first key ending with `suffix`, unquoted.
+    fn text(&self, suffix: &str) -> Option<String> {
+        self.values
+            .iter()
+            .find(|(key, _)| key.ends_with(suffix))
+            .map(|(_, value)| value.trim_matches('"').to_string())
+    }
+
+    /// The items of the list value of `key`, e.g. `("YDim","XDim")`.
+    fn list(&self, key: &str) -> Option<Vec<String>> {
+        let value = self.text(key)?;
+        let items = value.strip_prefix('(')?.strip_suffix(')')?;
+        Some(
+            items
+                .split(',')
+                .map(|item| item.trim().trim_matches('"').to_string())
+                .collect(),
+        )
+    }
+}
+
+/// A field of a grid, swath, point or zonal average of an HDF-EOS file.
+struct EosField {
+    structure: &'static str,
+    name: Option<String>,
+    field: Option<String>,
+    /// `data` or `geo`, the geolocation fields of swaths.
+    kind: Option<&'static str>,
+    dtype: Option<String>,
+    dimensions: Option<Vec<String>>,
+    /// The sizes of the dimensions, if all of them are defined.
+    shape: Option<Vec<u64>>,
+}
+
+/// The fields described by the structure metadata `root`. A structure without
+/// fields is a row of its own.
+fn eos_fields(root: &Node) -> Vec<EosField> {
+    let mut fields = vec![];
+    for group in &root.children {
+        let structure = match group.name.as_str() {
+            "GridStructure" => "grid",
+            "SwathStructure" => "swath",
+            "PointStructure" => "point",
+            "ZaStructure" => "zonal",
+            _ => continue,
+        };
+        for object in &group.children {
+            let name = object.text("Name");
+            // Grids define `XDim` and `YDim` directly.
+            let mut sizes: Vec<(String, u64)> = ["XDim", "YDim"]
+                .into_iter()
+                .filter_map(|dim| {
+                    let size = object.values.iter().find(|(key, _)| key == dim)?;
+                    Some((dim.to_string(), size.1.parse().ok()?))
+                })
+                .collect();
+            for dims in object.children.iter().filter(|c| c.name == "Dimension") {
+                for dim in &dims.children {
+                    if let (Some(dim), Some(Ok(size))) = (
+                        dim.text("DimensionName"),
+                        dim.text("Size").map(|size| size.parse()),
+                    ) {
+                        sizes.push((dim, size));
+                    }
+                }
+            }
+            let start = fields.len();
+            for group in object.children.iter().filter(|c| c.name.ends_with("Field")) {
+                for field in &group.children {
+                    let dimensions = field.list("DimList");
+                    let shape = dimensions.as_ref().and_then(|dims| {
+                        dims.iter()
+                            .map(|dim| sizes.iter().find(|(d, _)| d == dim).map(|(_, s)| *s))
+                            .collect()
+                    });
+                    fields.push(EosField {
+                        structure,
+                        name: name.clone(),
+                        field: field.text("FieldName"),
+                        kind: Some(if group.name == "GeoField" {
+                            "geo"
+                        } else {
+                            "data"
+                        }),
+                        dtype: field.text("DataType"),
+                        dimensions,
+                        shape,
+                    });
+                }
+            }
+            if fields.len() == start {
+                fields.push(EosField {
+                    structure,
+                    name,
+                    field: None,
+                    kind: None,
+                    dtype: None,
+                    dimensions: None,
+                    shape: None,
+                });
+            }
+        }
+    }
+    fields
+}
+
+/// The text of a scalar string attribute or dataset.
+fn read_text<T>(container: &T) -> Result<String, Box<dyn Error>>
+where
+    T: ReadRawBytes + Deref<Target = Container>,
+{
+    Ok(match container.dtype()?.to_descriptor()? {
+        TypeDescriptor::VarLenAscii => container.read_scalar::<VarLenAscii>()?.to_string(),
+        TypeDescriptor::VarLenUnicode => container.read_scalar::<VarLenUnicode>()?.to_string(),
+        dtype @ (TypeDescriptor::FixedAscii(_) | TypeDescriptor::FixedUnicode(_)) => {
+            let bytes = container.read_raw_bytes(&dtype)?;
+            let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end]).into_owned()
+        }
+        _ => return Err("StructMetadata is not a string".into()),
+    })
+}
+
+/// The structure metadata of an HDF-EOS file: the `StructMetadata.0`,
+/// `StructMetadata.1`... attributes of the root group, or datasets of the
+/// `HDFEOS INFORMATION` group in HDF-EOS5, joined.
+fn read_struct_metadata(file: &hdf5::File, path: &str) -> Result<String, Box<dyn Error>> {
+    let attrs = file.attr_names()?;
+    let info = file.group("HDFEOS INFORMATION").ok();
+    let mut text = String::new();
+    for i in 0.. {
+        let name = format!("StructMetadata.{i}");
+        let part = if attrs.contains(&name) {
+            read_text(&file.attr(&name)?)?
+        } else {
+            match info.as_ref().and_then(|info| info.dataset(&name).ok()) {
+                Some(dataset) => read_text(&dataset)?,
+                None => break,
+            }
+        };
+        text.push_str(&part);
+    }
+    if text.is_empty() {
+        return Err(format!("'{path}' has no HDF-EOS StructMetadata.0").into());
+    }
+    Ok(text)
+}
+
+pub struct Hdf5EosStructureBindData {
+    fields: Vec<EosField>,
+}
+
+pub struct Hdf5EosStructureInitData {
+    /// The index of the next vector to emit.
+    chunk: AtomicUsize,
+}
+
+/// `read_hdfeos_structure(path)`: the grids, swaths, points and zonal
+/// averages of an HDF-EOS file, with their fields and dimensions.
+pub(super) struct Hdf5EosStructure;
+
+impl VTab for Hdf5EosStructure {
+    type InitData = Hdf5EosStructureInitData;
+    type BindData = Hdf5EosStructureBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let file = open_path(&path)?;
+        let fields = eos_fields(&Node::parse(&read_struct_metadata(&file, &path)?)?);
+        let list = |ty: LogicalTypeId| LogicalTypeHandle::list(&ty.into());
+        bind.add_result_column("structure", LogicalTypeId::Varchar.into());
+        bind.add_result_column("name", LogicalTypeId::Varchar.into());
+        bind.add_result_column("field", LogicalTypeId::Varchar.into());
+        bind.add_result_column("kind", LogicalTypeId::Varchar.into());
+        bind.add_result_column("dtype", LogicalTypeId::Varchar.into());
+        bind.add_result_column("dimensions", list(LogicalTypeId::Varchar));
+        bind.add_result_column("shape", list(LogicalTypeId::UBigint));
+        bind.set_cardinality(fields.len() as _, true);
+        Ok(Hdf5EosStructureBindData { fields })
+    }
+
+    fn init(_: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Ok(Hdf5EosStructureInitData {
+            chunk: AtomicUsize::new(0),
+        })
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let fields = &func.get_bind_data().fields;
+        let capacity = unsafe { ffi::duckdb_vector_size() } as usize;
+        let chunk = func.get_init_data().chunk.fetch_add(1, Ordering::Relaxed);
+        let start = (chunk * capacity).min(fields.len());
+        let count = (fields.len() - start).min(capacity);
+        let column =
+            |idx: usize| unsafe { ffi::duckdb_data_chunk_get_vector(output.get_ptr(), idx as _) };
+        for (row, f) in fields[start..start + count].iter().enumerate() {
+            output.flat_vector(0).insert(row, f.structure);
+            let texts = [&f.name, &f.field, &f.kind.map(str::to_string), &f.dtype];
+            for (col, text) in texts.into_iter().enumerate() {
+                let mut vector = output.flat_vector(col + 1);
+                match text {
+                    Some(text) => vector.insert(row, text.as_str()),
+                    None => vector.set_null(row),
+                }
+            }
+            match &f.dimensions {
+                Some(dims) => fill_list(column(5), row, dims, |v, i, d| v.insert(i, d.as_str())),
+                None => output.flat_vector(5).set_null(row),
+            }
+            match &f.shape {
+                Some(shape) => fill_list(column(6), row, shape, |v, i, s| {
+                    v.as_mut_slice::<u64>()[i] = *s
+                }),
+                None => output.flat_vector(6).set_null(row),
+            }
+        }
+        output.set_len(count);
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![LogicalTypeId::Varchar.into()])
+    }
+}
@@ -0,0 +1,93 @@
+use std::{error::Error, fmt};
+
+/// The failures of reading a dataset that a caller may tell apart, rather
+/// than by their message.
+#[derive(Debug)]
+pub enum Hdf5ReadError {
+    /// There is no file at `path`.
+    FileNotFound { path: String },
+    /// HDF5 can't open the file at `path`, e.g. as it isn't an HDF5 file.
+    FileOpen { path: String, source: hdf5::Error },
+    /// The file at `path` has no dataset at `dataset`.
+    MissingDataset { path: String, dataset: String },
+    /// A datatype of `dataset`, or of one of its fields, that has no DuckDB
+    /// type.
+    UnsupportedType {
+        dataset: String,
+        field: Option<String>,
+        dtype: String,
+    },
+    /// A selection that doesn't fit the shape of its dataset.
+    Selection(String),
+    /// Items or a file beyond a size limit, as described.
+    TooLarge(String),
+    /// Reading the file at `path` failed.
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    /// Another failure of HDF5.
+    Hdf5(hdf5::Error),
+}
+
+impl Hdf5ReadError {
+    /// The failure of reading the file at `path`, which may not exist.
+    pub fn io(path: &str, source: std::io::Error) -> Self {
+        let path = path.to_string();
+        match source.kind() {
+            std::io::ErrorKind::NotFound => Self::FileNotFound { path },
+            _ => Self::Io { path, source },
+        }
+    }
+}
+
+impl fmt::Display for Hdf5ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileNotFound { path } => write!(f, "file '{path}' not found"),
+            Self::FileOpen { path, source } => write!(f, "cannot open '{path}': {source}"),
+            Self::MissingDataset { path, dataset } => {
+                write!(f, "dataset '{dataset}' not found in '{path}'")
+            }
+            Self::UnsupportedType {
+                dataset,
+                field,
+                dtype,
+            } => match field {
+                Some(field) => write!(
+                    f,
+                    "unsupported HDF5 type: {dtype} of field '{field}' of '{dataset}'"
+                ),
+                None => write!(f, "unsupported HDF5 type: {dtype} of '{dataset}'"),
+            },
+            Self::Selection(message) | Self::TooLarge(message) => f.write_str(message),
+            Self::Io { path, source } => write!(f, "cannot read '{path}': {source}"),
+            Self::Hdf5(source) => source.fmt(f),
+        }
+    }
+}
+
+impl Error for Hdf5ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::FileOpen { source, .. } | Self::Hdf5(source) => Some(source),
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<hdf5::Error> for Hdf5ReadError {
+    fn from(err: hdf5::Error) -> Self {
+        Self::Hdf5(err)
+    }
+}
+
+impl From<Hdf5ReadError> for hdf5::Error {
+    fn from(err: Hdf5ReadError) -> Self {
+        match err {
+            Hdf5ReadError::Hdf5(err) => err,
+            err => err.to_string().into(),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use super::{open_dataset, open_path, Hdf5ReadBindData, Hdf5ReadInitData, Hdf5ReadOptions};
+use duckdb::{
+    core::{DataChunkHandle, LogicalTypeHandle, LogicalTypeId},
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use std::error::Error;
+
+/// Bind `function(path, dataset, n)` to the first `n` items along the outer
+/// axis of the dataset, or the last ones with `tail`, as `read_hdf5` reads
+/// them with `offset` and `count`. A dataset with fewer items is read whole.
+fn bind_outer(
+    bind: &BindInfo,
+    function: &str,
+    tail: bool,
+) -> Result<Hdf5ReadBindData, Box<dyn Error>> {
+    let path = bind.get_parameter(0).to_string();
+    let dataset = bind.get_parameter(1).to_string();
+    let n = bind.get_parameter(2).to_int64();
+    let n = u64::try_from(n)
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| format!("{function} requires a positive n, got {n}"))?;
+    let shape = {
+        let file = open_path(&path)?;
+        open_dataset(&file, &dataset, &path)?.shape()
+    };
+    let mut options = Hdf5ReadOptions::from_bind(bind)?;
+    // A scalar dataset has a single item and no axis to select along.
+    if let Some(len) = shape.first().map(|len| *len as u64) {
+        let mut offset = vec![0; shape.len()];
+        let mut count: Vec<u64> = shape.iter().map(|len| *len as u64).collect();
+        count[0] = n.min(len);
+        if tail {
+            offset[0] = len - count[0];
+        }
+        options.hyperslab.offset = Some(offset);
+        options.hyperslab.count = Some(count);
+    }
+    let data = Hdf5ReadBindData::new(&path, Some(&dataset), &options)?;
+    for (name, dtype) in data.iter_dtype() {
+        bind.add_result_column(&name, dtype);
+    }
+    bind.set_cardinality(data.len() as _, true);
+    Ok(data)
+}
+
+fn parameters() -> Vec<LogicalTypeHandle> {
+    vec![
+        LogicalTypeId::Varchar.into(),
+        LogicalTypeId::Varchar.into(),
+        LogicalTypeId::Bigint.into(),
+    ]
+}
+
+/// `hdf5_head(path, dataset, n)`: the first `n` items of a dataset.
+pub(super) struct Hdf5Head;
+
+impl VTab for Hdf5Head {
+    type InitData = Hdf5ReadInitData;
+    type BindData = Hdf5ReadBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        bind_outer(bind, "hdf5_head", false)
+    }
+
+    fn init(init: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        let bind_data = unsafe { init.get_bind_data::<Self::BindData>().as_ref() }.unwrap();
+        Ok(bind_data.project(&init.get_column_indices()))
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        bind_data.scan(init_data, output)?;
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(parameters())
+    }
+
+    fn supports_pushdown() -> bool {
+        true
+    }
+}
+
+/// `hdf5_tail(path, dataset, n)`: the last `n` items of a dataset, in order.
+pub(super) struct Hdf5Tail;
+
+impl VTab for Hdf5Tail {
+    type InitData = Hdf5ReadInitData;
+    type BindData = Hdf5ReadBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        bind_outer(bind, "hdf5_tail", true)
+    }
+
+    fn init(init: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        let bind_data = unsafe { init.get_bind_data::<Self::BindData>().as_ref() }.unwrap();
+        Ok(bind_data.project(&init.get_column_indices()))
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        bind_data.scan(init_data, output)?;
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(parameters())
+    }
+
+    fn supports_pushdown() -> bool {
+        true
+    }
+}
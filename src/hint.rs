@@ -0,0 +1,107 @@
+use super::parse_cast_type;
+use hdf5::types::TypeDescriptor;
+use serde_json::Value;
+use std::error::Error;
+
+/// How a column marked by `schema_hint` is written instead of as a number.
+#[derive(Debug, Clone)]
+pub(super) enum Mark {
+    /// A time since the epoch, counting this many units per second.
+    Time(i64),
+    /// A label for each value.
+    Labels(Vec<(i64, String)>),
+    /// The labels of the members of an HDF5 enum.
+    Enum,
+}
+
+/// How `schema_hint` reads one column of a dataset.
+#[derive(Debug)]
+pub(super) struct ColumnHint {
+    /// The field name of the column, or `result`.
+    pub column: String,
+    pub name: Option<String>,
+    pub dtype: Option<TypeDescriptor>,
+    pub mark: Option<Mark>,
+}
+
+impl ColumnHint {
+    fn parse(column: String, hint: Value) -> Result<Self, Box<dyn Error>> {
+        let Value::Object(keys) = hint else {
+            return Err(format!("schema_hint of '{column}' must be an object").into());
+        };
+        let mut hint = Self {
+            column,
+            name: None,
+            dtype: None,
+            mark: None,
+        };
+        let column = &hint.column;
+        for (key, value) in keys {
+            let mark = match (key.as_str(), value) {
+                ("name", Value::String(name)) => {
+                    hint.name = Some(name);
+                    continue;
+                }
+                ("type", Value::String(ty)) => {
+                    hint.dtype = Some(parse_cast_type(&ty)?);
+                    continue;
+                }
+                ("time", Value::String(unit)) => Mark::Time(match unit.as_str() {
+                    "s" => 1,
+                    "ms" => 1_000,
+                    "us" => 1_000_000,
+                    "ns" => 1_000_000_000,
+                    _ => {
+                        return Err(format!(
+                            "invalid time unit '{unit}' of '{column}' in schema_hint, \
+                             expected s, ms, us or ns"
+                        )
+                        .into())
+                    }
+                }),
+                ("enum", Value::Bool(true)) => Mark::Enum,
+                ("enum", Value::Object(labels)) => Mark::Labels(
+                    labels
+                        .into_iter()
+                        .map(|(value, label)| match (value.parse(), label) {
+                            (Ok(value), Value::String(label)) => Ok((value, label)),
+                            _ => Err(format!("invalid enum label of '{column}' in schema_hint")),
+                        })
+                        .collect::<Result<_, _>>()?,
+                ),
+                (key @ ("name" | "type" | "time" | "enum"), _) => {
+                    return Err(format!("invalid {key} of '{column}' in schema_hint").into())
+                }
+                (key, _) => {
+                    return Err(format!("unknown key '{key}' of '{column}' in schema_hint").into())
+                }
+            };
+            if hint.mark.replace(mark).is_some() {
+                return Err(
+                    format!("'{column}' cannot be both a time and an enum in schema_hint").into(),
+                );
+            }
+        }
+        Ok(hint)
+    }
+}
+
+/// Parse `schema_hint`: a JSON object, or the path of a file with one, from
+/// the columns of a dataset to their hints, e.g.
+/// `{"ts": {"name": "time", "time": "s"}, "flag": {"enum": {"0": "off"}}}`.
+pub(super) fn parse_schema_hint(s: &str) -> Result<Vec<ColumnHint>, Box<dyn Error>> {
+    let json = if s.trim_start().starts_with('{') {
+        s.to_string()
+    } else {
+        std::fs::read_to_string(s).map_err(|e| format!("cannot read schema_hint '{s}': {e}"))?
+    };
+    let Value::Object(columns) =
+        serde_json::from_str(&json).map_err(|e| format!("invalid schema_hint: {e}"))?
+    else {
+        return Err("schema_hint must be a JSON object".into());
+    };
+    columns
+        .into_iter()
+        .map(|(column, hint)| ColumnHint::parse(column, hint))
+        .collect()
+}
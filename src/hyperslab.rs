@@ -0,0 +1,304 @@
+use super::{datatype, Hdf5ReadError, ReadRawBytes};
+use hdf5::types::TypeDescriptor;
+use hdf5_sys::{h5d, h5i::hid_t, h5p, h5s};
+use std::error::Error;
+
+/// Parse a list of indices, as rendered by DuckDB, e.g. `[0, 3]`.
+pub fn parse_indices(name: &str, s: &str) -> Result<Vec<u64>, Box<dyn Error>> {
+    let items = s
+        .trim()
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .ok_or_else(|| format!("{name} must be a list"))?;
+    items
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            item.parse().map_err(|_| {
+                format!("invalid {name} '{item}', expected a non-negative integer").into()
+            })
+        })
+        .collect()
+}
+
+/// A strided selection of a dataset, per axis. Missing parts select from the
+/// start, with a stride of 1 and up to the end.
+#[derive(Debug, Default, Clone)]
+pub struct Hyperslab {
+    pub offset: Option<Vec<u64>>,
+    pub count: Option<Vec<u64>>,
+    pub stride: Option<Vec<u64>>,
+}
+
+/// A hyperslab validated against the shape of a dataset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub offset: Vec<u64>,
+    pub count: Vec<u64>,
+    pub stride: Vec<u64>,
+}
+
+impl Hyperslab {
+    pub fn is_empty(&self) -> bool {
+        self.offset.is_none() && self.count.is_none() && self.stride.is_none()
+    }
+
+    /// Fill in the defaults and check the selection fits in `shape`.
+    pub fn resolve(&self, shape: &[usize]) -> Result<Selection, Hdf5ReadError> {
+        let rank = shape.len();
+        let check = |name: &str, values: &Option<Vec<u64>>| match values {
+            Some(values) if values.len() != rank => Err(Hdf5ReadError::Selection(format!(
+                "{name} has {} dimensions, but the dataset has {rank}",
+                values.len()
+            ))),
+            _ => Ok(()),
+        };
+        check("offset", &self.offset)?;
+        check("count", &self.count)?;
+        check("stride", &self.stride)?;
+        let offset = self.offset.clone().unwrap_or_else(|| vec![0; rank]);
+        let stride = self.stride.clone().unwrap_or_else(|| vec![1; rank]);
+        let mut count = vec![];
+        for axis in 0..rank {
+            let dim = shape[axis] as u64;
+            if stride[axis] == 0 {
+                return Err(Hdf5ReadError::Selection(format!(
+                    "stride of axis {axis} must be positive"
+                )));
+            }
+            if offset[axis] > dim {
+                return Err(Hdf5ReadError::Selection(format!(
+                    "offset {} is out of axis {axis} of size {dim}",
+                    offset[axis]
+                )));
+            }
+            let available = (dim - offset[axis]).div_ceil(stride[axis]);
+            let axis_count = match &self.count {
+                Some(c) if c[axis] > available => {
+                    return Err(Hdf5ReadError::Selection(format!(
+                        "count {} exceeds the {available} items available along axis {axis}",
+                        c[axis]
+                    )))
+                }
+                Some(c) => c[axis],
+                None => available,
+            };
+            count.push(axis_count);
+        }
+        Ok(Selection {
+            offset,
+            count,
+            stride,
+        })
+    }
+
+    /// Resolve the selection against `shape` without its axes of size 1,
+    /// which are selected whole.
+    pub fn resolve_squeezed(&self, shape: &[usize]) -> Result<Selection, Hdf5ReadError> {
+        let axes: Vec<usize> = (0..shape.len()).filter(|axis| shape[*axis] != 1).collect();
+        let parts = [
+            ("offset", &self.offset),
+            ("count", &self.count),
+            ("stride", &self.stride),
+        ];
+        for (name, values) in parts {
+            if let Some(values) = values.as_ref().filter(|v| v.len() != axes.len()) {
+                return Err(Hdf5ReadError::Selection(format!(
+                    "{name} has {} dimensions, but the dataset has {} without its axes of size 1",
+                    values.len(),
+                    axes.len()
+                )));
+            }
+        }
+        let squeezed = self.resolve(&axes.iter().map(|axis| shape[*axis]).collect::<Vec<_>>())?;
+        let mut selection = Selection {
+            offset: vec![0; shape.len()],
+            count: vec![1; shape.len()],
+            stride: vec![1; shape.len()],
+        };
+        for (index, axis) in axes.into_iter().enumerate() {
+            selection.offset[axis] = squeezed.offset[index];
+            selection.count[axis] = squeezed.count[index];
+            selection.stride[axis] = squeezed.stride[index];
+        }
+        Ok(selection)
+    }
+}
+
+impl Selection {
+    /// The number of selected items.
+    pub fn len(&self) -> usize {
+        self.count.iter().product::<u64>() as usize
+    }
+
+    /// The dataspace of `dataset` with the selected items selected.
+    pub fn select(&self, dataset: &hdf5::Dataset) -> hdf5::Result<hdf5::Dataspace> {
+        let file_space = dataset.space()?;
+        if self.len() > 0 {
+            hdf5::h5call!(h5s::H5Sselect_hyperslab(
+                file_space.id(),
+                h5s::H5S_SELECT_SET,
+                self.offset.as_ptr(),
+                self.stride.as_ptr(),
+                self.count.as_ptr(),
+                std::ptr::null()
+            ))?;
+        }
+        Ok(file_space)
+    }
+
+    /// Read the selected items in row-major order. An axis with a count of 1
+    /// drops out of the result.
+    pub fn read(&self, dataset: &hdf5::Dataset, dtype: &TypeDescriptor) -> hdf5::Result<Vec<u8>> {
+        read_selected(dataset, &self.select(dataset)?, self.len(), dtype)
+    }
+}
+
+/// Read the items of `dataset` where `mask`, a dataset of the same shape, is
+/// nonzero, in row-major order.
+pub fn read_masked(
+    dataset: &hdf5::Dataset,
+    mask: &hdf5::Dataset,
+    dtype: &TypeDescriptor,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let (file_space, len) = mask_space(dataset, mask)?;
+    Ok(read_selected(dataset, &file_space, len, dtype)?)
+}
+
+/// The dataspace of `dataset` with the items where `mask` is nonzero
+/// selected, and their number.
+pub fn mask_space(
+    dataset: &hdf5::Dataset,
+    mask: &hdf5::Dataset,
+) -> Result<(hdf5::Dataspace, usize), Box<dyn Error>> {
+    let shape = dataset.shape();
+    if mask.shape() != shape {
+        return Err(format!(
+            "mask '{}' has shape {:?}, but '{}' has shape {:?}",
+            mask.name(),
+            mask.shape(),
+            dataset.name(),
+            shape
+        )
+        .into());
+    }
+    let mask_dtype = mask.dtype()?.to_descriptor()?;
+    if !matches!(
+        mask_dtype,
+        TypeDescriptor::Boolean | TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_)
+    ) {
+        return Err(format!("mask '{}' must be boolean or integer", mask.name()).into());
+    }
+    let values = mask.read_raw_bytes(&mask_dtype)?;
+    // The coordinates of every selected item, axis by axis.
+    let mut coords = vec![];
+    for (index, value) in values.chunks_exact(mask_dtype.size()).enumerate() {
+        if value.iter().any(|b| *b != 0) {
+            push_coords(index, &shape, &mut coords);
+        }
+    }
+    Ok(elements_space(dataset, &coords)?)
+}
+
+/// Read the `len` items of `dataset` from the item `start` on, in row-major
+/// order.
+pub fn read_range(
+    dataset: &hdf5::Dataset,
+    start: usize,
+    len: usize,
+    dtype: &TypeDescriptor,
+) -> hdf5::Result<Vec<u8>> {
+    let shape = dataset.shape();
+    let mut coords = Vec::with_capacity(len * shape.len());
+    for index in start..start + len {
+        push_coords(index, &shape, &mut coords);
+    }
+    read_elements(dataset, &coords, dtype)
+}
+
+/// Append the coordinates of the row-major `index` in `shape`, axis by axis.
+fn push_coords(index: usize, shape: &[usize], coords: &mut Vec<u64>) {
+    let start = coords.len();
+    let mut rest = index;
+    for dim in shape.iter().rev() {
+        coords.push((rest % dim) as u64);
+        rest /= dim;
+    }
+    coords[start..].reverse();
+}
+
+/// Read the items of `dataset` at `coords`, the coordinates of every item.
+fn read_elements(
+    dataset: &hdf5::Dataset,
+    coords: &[u64],
+    dtype: &TypeDescriptor,
+) -> hdf5::Result<Vec<u8>> {
+    let (file_space, len) = elements_space(dataset, coords)?;
+    read_selected(dataset, &file_space, len, dtype)
+}
+
+/// The dataspace of `dataset` with the items at `coords` selected, and
+/// their number.
+fn elements_space(
+    dataset: &hdf5::Dataset,
+    coords: &[u64],
+) -> hdf5::Result<(hdf5::Dataspace, usize)> {
+    let len = coords.len() / dataset.ndim().max(1);
+    let file_space = dataset.space()?;
+    if len > 0 {
+        hdf5::h5call!(h5s::H5Sselect_elements(
+            file_space.id(),
+            h5s::H5S_SELECT_SET,
+            len,
+            coords.as_ptr()
+        ))?;
+    }
+    Ok((file_space, len))
+}
+
+/// Read the `len` items of `dataset` selected in `file_space`.
+pub fn read_selected(
+    dataset: &hdf5::Dataset,
+    file_space: &hdf5::Dataspace,
+    len: usize,
+    dtype: &TypeDescriptor,
+) -> hdf5::Result<Vec<u8>> {
+    read_selected_with(dataset, file_space, len, dtype, h5p::H5P_DEFAULT)
+}
+
+/// Read the `len` items of `dataset` selected in `file_space`, with the
+/// transfer property list `dxpl`.
+pub fn read_selected_with(
+    dataset: &hdf5::Dataset,
+    file_space: &hdf5::Dataspace,
+    len: usize,
+    dtype: &TypeDescriptor,
+    dxpl: hid_t,
+) -> hdf5::Result<Vec<u8>> {
+    let mut buffer = Vec::with_capacity(len * dtype.size());
+    if len == 0 {
+        return Ok(buffer);
+    }
+    let native_dtype = datatype::memory_type(dtype, &dataset.dtype()?)?;
+    let dims = [len as u64];
+    let mem_space: hdf5::Dataspace = unsafe {
+        hdf5::from_id(hdf5::h5call!(h5s::H5Screate_simple(
+            1,
+            dims.as_ptr(),
+            std::ptr::null()
+        ))?)?
+    };
+    hdf5::h5call!(h5d::H5Dread(
+        dataset.id(),
+        native_dtype.id(),
+        mem_space.id(),
+        file_space.id(),
+        dxpl,
+        buffer.spare_capacity_mut().as_mut_ptr() as *mut _
+    ))?;
+    unsafe {
+        buffer.set_len(len * dtype.size());
+    }
+    Ok(buffer)
+}
@@ -0,0 +1,328 @@
+use super::{
+    datatype_name, json::write_string, nexus::read_strings, open_dataset, open_path,
+    virtual_sources,
+};
+use duckdb::{
+    core::{DataChunkHandle, FlatVector, Inserter, ListVector, LogicalTypeHandle, LogicalTypeId},
+    ffi,
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use hdf5::{
+    filters::Filter,
+    plist::dataset_create::{ExternalFile, Layout},
+};
+use hdf5_sys::h5t;
+use std::{
+    error::Error,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+fn filter_name(filter: &Filter) -> String {
+    match filter {
+        Filter::Deflate(level) => format!("deflate({level})"),
+        Filter::Shuffle => "shuffle".to_string(),
+        Filter::Fletcher32 => "fletcher32".to_string(),
+        Filter::SZip(..) => "szip".to_string(),
+        Filter::NBit => "nbit".to_string(),
+        Filter::ScaleOffset(_) => "scaleoffset".to_string(),
+        Filter::User(id, _) => format!("filter({id})"),
+    }
+}
+
+/// Write `values` as the list entry at `row`, with `insert` writing one item.
+pub(super) fn fill_list<T>(
+    vector: ffi::duckdb_vector,
+    row: usize,
+    values: &[T],
+    insert: impl Fn(&mut FlatVector, usize, &T),
+) {
+    let mut list = ListVector::from(vector);
+    let offset = list.len();
+    unsafe { ffi::duckdb_list_vector_reserve(vector, (offset + values.len()) as _) };
+    let mut child = FlatVector::from(unsafe { ffi::duckdb_list_vector_get_child(vector) });
+    for (i, value) in values.iter().enumerate() {
+        insert(&mut child, offset + i, value);
+    }
+    list.set_len(offset + values.len());
+    list.set_entry(row, offset, values.len());
+}
+
+pub struct Hdf5DatasetInfoBindData {
+    name: String,
+    dtype: String,
+    shape: Vec<u64>,
+    chunks: Option<Vec<u64>>,
+    filters: Vec<String>,
+    fletcher32: bool,
+    layout: &'static str,
+    /// The sources of a virtual dataset, as `file:dataset`.
+    sources: Option<Vec<String>>,
+    /// The sizes of an item on disk and as the native type in memory.
+    dtype_size: u64,
+    native_size: Option<u64>,
+    /// Whether the items are packed into their significant bits.
+    nbit: bool,
+    /// `null` for no items at all, `scalar` for one, or `simple`.
+    dataspace: &'static str,
+    /// `ctime`, `mtime`, `atime` and `btime`, in seconds since the epoch;
+    /// `None` when the file doesn't track them.
+    times: [Option<i64>; 4],
+    /// The files of a dataset with external storage.
+    external: Option<Vec<ExternalFile>>,
+    /// The labels of the dimensions set with `H5DSset_label`, `None` for
+    /// those without one.
+    dim_labels: Option<Vec<Option<String>>>,
+}
+
+impl Hdf5DatasetInfoBindData {
+    fn new(path: &str, dataset: &str) -> hdf5::Result<Self> {
+        let file = open_path(path)?;
+        Self::from_dataset(&open_dataset(&file, dataset, path)?)
+    }
+
+    pub(super) fn from_dataset(dataset: &hdf5::Dataset) -> hdf5::Result<Self> {
+        let filters = dataset.filters();
+        let dtype = dataset.dtype()?;
+        // Not every datatype, e.g. a reference, has a native equivalent.
+        let native_size = hdf5::h5lock!({
+            let native = h5t::H5Tget_native_type(dtype.id(), h5t::H5T_direction_t::H5T_DIR_ASCEND);
+            (native >= 0).then(|| {
+                let size = h5t::H5Tget_size(native);
+                h5t::H5Tclose(native);
+                size as u64
+            })
+        });
+        let layout = dataset.layout();
+        let space = dataset.space()?;
+        let info = dataset.loc_info()?;
+        let sources = match layout {
+            Layout::Virtual => Some(
+                virtual_sources(dataset)?
+                    .into_iter()
+                    .map(|(file, dataset)| format!("{file}:{dataset}"))
+                    .collect(),
+            ),
+            _ => None,
+        };
+        let external = dataset.dcpl()?.get_external()?;
+        // The dimension scale API keeps the labels in an attribute, with
+        // empty strings for the dimensions without one.
+        let dim_labels = read_strings(dataset, "DIMENSION_LABELS")
+            .map_err(|e| hdf5::Error::from(e.to_string()))?
+            .map(|labels| {
+                labels
+                    .into_iter()
+                    .map(|label| (!label.is_empty()).then_some(label))
+                    .collect()
+            });
+        Ok(Self {
+            name: dataset.name(),
+            dtype: datatype_name(&dtype),
+            shape: dataset.shape().into_iter().map(|d| d as u64).collect(),
+            chunks: dataset
+                .chunk()
+                .map(|chunk| chunk.into_iter().map(|d| d as u64).collect()),
+            fletcher32: filters.contains(&Filter::Fletcher32),
+            filters: filters.iter().map(filter_name).collect(),
+            layout: match layout {
+                Layout::Compact => "compact",
+                Layout::Contiguous => "contiguous",
+                Layout::Chunked => "chunked",
+                Layout::Virtual => "virtual",
+            },
+            sources,
+            dtype_size: dtype.size() as u64,
+            native_size,
+            nbit: filters.contains(&Filter::NBit),
+            dataspace: if space.is_null() {
+                "null"
+            } else if space.is_scalar() {
+                "scalar"
+            } else {
+                "simple"
+            },
+            times: [info.ctime, info.mtime, info.atime, info.btime].map(|t| (t > 0).then_some(t)),
+            external: (!external.is_empty()).then_some(external),
+            dim_labels,
+        })
+    }
+
+    /// Append the datatype, shape, chunks, filters, layout and dataspace to
+    /// `json`, as the fields of an object.
+    pub(super) fn write_json_fields(&self, json: &mut String) {
+        let list = |json: &mut String, items: &[u64]| {
+            let items: Vec<String> = items.iter().map(u64::to_string).collect();
+            json.push('[');
+            json.push_str(&items.join(","));
+            json.push(']');
+        };
+        json.push_str("\"dtype\":");
+        write_string(json, &self.dtype);
+        json.push_str(",\"shape\":");
+        list(json, &self.shape);
+        json.push_str(",\"chunks\":");
+        match &self.chunks {
+            Some(chunks) => list(json, chunks),
+            None => json.push_str("null"),
+        }
+        json.push_str(",\"filters\":[");
+        for (i, filter) in self.filters.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            write_string(json, filter);
+        }
+        json.push_str("],\"layout\":");
+        write_string(json, self.layout);
+        json.push_str(",\"dataspace\":");
+        write_string(json, self.dataspace);
+    }
+
+    fn columns() -> Vec<(&'static str, LogicalTypeHandle)> {
+        let list = |ty: LogicalTypeId| LogicalTypeHandle::list(&ty.into());
+        vec![
+            ("dataset", LogicalTypeId::Varchar.into()),
+            ("dtype", LogicalTypeId::Varchar.into()),
+            ("shape", list(LogicalTypeId::UBigint)),
+            ("chunks", list(LogicalTypeId::UBigint)),
+            ("filters", list(LogicalTypeId::Varchar)),
+            ("fletcher32", LogicalTypeId::Boolean.into()),
+            ("layout", LogicalTypeId::Varchar.into()),
+            ("sources", list(LogicalTypeId::Varchar)),
+            ("dtype_size", LogicalTypeId::UBigint.into()),
+            ("native_size", LogicalTypeId::UBigint.into()),
+            ("nbit", LogicalTypeId::Boolean.into()),
+            ("dataspace", LogicalTypeId::Varchar.into()),
+            ("ctime", LogicalTypeId::Timestamp.into()),
+            ("mtime", LogicalTypeId::Timestamp.into()),
+            ("atime", LogicalTypeId::Timestamp.into()),
+            ("btime", LogicalTypeId::Timestamp.into()),
+            (
+                "external",
+                LogicalTypeHandle::list(&LogicalTypeHandle::struct_type(&[
+                    ("file", LogicalTypeId::Varchar.into()),
+                    ("offset", LogicalTypeId::Bigint.into()),
+                    ("size", LogicalTypeId::UBigint.into()),
+                ])),
+            ),
+            ("dim_labels", list(LogicalTypeId::Varchar)),
+        ]
+    }
+
+    fn fill(&self, output: &mut DataChunkHandle) {
+        let column =
+            |idx: usize| unsafe { ffi::duckdb_data_chunk_get_vector(output.get_ptr(), idx as _) };
+        let write_u64 = |v: &mut FlatVector, i: usize, d: &u64| v.as_mut_slice::<u64>()[i] = *d;
+        output.flat_vector(0).insert(0, self.name.as_str());
+        output.flat_vector(1).insert(0, self.dtype.as_str());
+        fill_list(column(2), 0, &self.shape, write_u64);
+        match &self.chunks {
+            Some(chunks) => fill_list(column(3), 0, chunks, write_u64),
+            None => ListVector::from(column(3)).set_null(0),
+        }
+        fill_list(column(4), 0, &self.filters, |v, i, f| {
+            v.insert(i, f.as_str())
+        });
+        output.flat_vector(5).as_mut_slice::<bool>()[0] = self.fletcher32;
+        output.flat_vector(6).insert(0, self.layout);
+        match &self.sources {
+            Some(sources) => fill_list(column(7), 0, sources, |v, i, s| v.insert(i, s.as_str())),
+            None => ListVector::from(column(7)).set_null(0),
+        }
+        output.flat_vector(8).as_mut_slice::<u64>()[0] = self.dtype_size;
+        let mut native_size = output.flat_vector(9);
+        match self.native_size {
+            Some(size) => native_size.as_mut_slice::<u64>()[0] = size,
+            None => native_size.set_null(0),
+        }
+        output.flat_vector(10).as_mut_slice::<bool>()[0] = self.nbit;
+        output.flat_vector(11).insert(0, self.dataspace);
+        for (i, time) in self.times.iter().enumerate() {
+            let mut vector = output.flat_vector(12 + i);
+            match time {
+                // Timestamps are in microseconds.
+                Some(time) => vector.as_mut_slice::<i64>()[0] = time * 1_000_000,
+                None => vector.set_null(0),
+            }
+        }
+        let mut external = ListVector::from(column(16));
+        match &self.external {
+            Some(files) => {
+                let entries = external.struct_child(files.len());
+                let (names, mut offsets, mut sizes) = (
+                    entries.child(0, files.len()),
+                    entries.child(1, files.len()),
+                    entries.child(2, files.len()),
+                );
+                for (i, file) in files.iter().enumerate() {
+                    names.insert(i, file.name.as_str());
+                    offsets.as_mut_slice::<i64>()[i] = file.offset as _;
+                    // HDF5 reports a file up to its end with a size of 0.
+                    match file.size {
+                        0 => sizes.set_null(i),
+                        size => sizes.as_mut_slice::<u64>()[i] = size as _,
+                    }
+                }
+                external.set_len(files.len());
+                external.set_entry(0, 0, files.len());
+            }
+            None => external.set_null(0),
+        }
+        match &self.dim_labels {
+            Some(labels) => fill_list(column(17), 0, labels, |v, i, label| match label {
+                Some(label) => v.insert(i, label.as_str()),
+                None => v.set_null(i),
+            }),
+            None => ListVector::from(column(17)).set_null(0),
+        }
+        output.set_len(1);
+    }
+}
+
+pub struct Hdf5DatasetInfoInitData {
+    done: AtomicBool,
+}
+
+/// `hdf5_dataset_info(path, dataset)`: the storage details of a dataset.
+pub struct Hdf5DatasetInfo;
+
+impl VTab for Hdf5DatasetInfo {
+    type InitData = Hdf5DatasetInfoInitData;
+    type BindData = Hdf5DatasetInfoBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let dataset = bind.get_parameter(1).to_string();
+        let data = Hdf5DatasetInfoBindData::new(&path, &dataset)?;
+        for (name, ty) in Hdf5DatasetInfoBindData::columns() {
+            bind.add_result_column(name, ty);
+        }
+        bind.set_cardinality(1, true);
+        Ok(data)
+    }
+
+    fn init(_: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Ok(Hdf5DatasetInfoInitData {
+            done: AtomicBool::new(false),
+        })
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        if func.get_init_data().done.swap(true, Ordering::Relaxed) {
+            output.set_len(0);
+        } else {
+            func.get_bind_data().fill(output);
+        }
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+}
@@ -0,0 +1,222 @@
+use super::{
+    attributes::enum_label, dataset_len, open_dataset, open_path, vlen_elements, Hdf5FileData,
+    Hdf5ReadError, Hdf5ReadOptions,
+};
+use duckdb::{
+    core::{DataChunkHandle, Inserter, LogicalTypeHandle, LogicalTypeId},
+    ffi,
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use hdf5::types::{FloatSize, IntSize, TypeDescriptor, VarLenAscii, VarLenUnicode};
+use std::{
+    error::Error,
+    fmt::Write,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The default of `max_elements`, the most items rendered without a slice.
+const DEFAULT_MAX_ELEMENTS: usize = 1_000_000;
+
+/// Append `s` to `json` as a JSON string.
+pub(super) fn write_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c < ' ' => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// Append a float to `json`, as `null` if it is not finite.
+fn write_float(json: &mut String, value: f64) {
+    if value.is_finite() {
+        write!(json, "{value}").unwrap();
+    } else {
+        json.push_str("null");
+    }
+}
+
+/// Append one item of `dtype` to `json`: compounds as objects, arrays as
+/// arrays, and enums as their labels.
+pub(super) fn write_item(json: &mut String, dtype: &TypeDescriptor, slice: &[u8]) {
+    macro_rules! read {
+        ($t:ty) => {
+            unsafe { slice.as_ptr().cast::<$t>().read_unaligned() }
+        };
+    }
+    match dtype {
+        TypeDescriptor::Integer(IntSize::U1) => write!(json, "{}", read!(i8)).unwrap(),
+        TypeDescriptor::Integer(IntSize::U2) => write!(json, "{}", read!(i16)).unwrap(),
+        TypeDescriptor::Integer(IntSize::U4) => write!(json, "{}", read!(i32)).unwrap(),
+        TypeDescriptor::Integer(IntSize::U8) => write!(json, "{}", read!(i64)).unwrap(),
+        TypeDescriptor::Unsigned(IntSize::U1) => write!(json, "{}", read!(u8)).unwrap(),
+        TypeDescriptor::Unsigned(IntSize::U2) => write!(json, "{}", read!(u16)).unwrap(),
+        TypeDescriptor::Unsigned(IntSize::U4) => write!(json, "{}", read!(u32)).unwrap(),
+        TypeDescriptor::Unsigned(IntSize::U8) => write!(json, "{}", read!(u64)).unwrap(),
+        TypeDescriptor::Float(FloatSize::U4) => write_float(json, read!(f32) as f64),
+        TypeDescriptor::Float(FloatSize::U8) => write_float(json, read!(f64)),
+        TypeDescriptor::Boolean => json.push_str(if read!(u8) != 0 { "true" } else { "false" }),
+        TypeDescriptor::Enum(e) => match enum_label(e, &slice[..dtype.size()]) {
+            Some(label) => write_string(json, &label),
+            None => write_item(json, &e.base_type(), slice),
+        },
+        TypeDescriptor::Compound(c) => {
+            json.push('{');
+            for (i, f) in c.fields.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_string(json, &f.name);
+                json.push(':');
+                write_item(json, &f.ty, &slice[f.offset..]);
+            }
+            json.push('}');
+        }
+        TypeDescriptor::FixedArray(ty, len) => write_array(json, ty, slice, *len),
+        TypeDescriptor::VarLenArray(ty) => {
+            let data = vlen_elements(ty, slice);
+            write_array(json, ty, data, data.len() / ty.size());
+        }
+        TypeDescriptor::FixedAscii(len) | TypeDescriptor::FixedUnicode(len) => {
+            let data = &slice[..*len];
+            let end = data.iter().position(|&b| b == 0).unwrap_or(*len);
+            write_string(json, &String::from_utf8_lossy(&data[..end]));
+        }
+        TypeDescriptor::VarLenAscii => {
+            let s = unsafe { slice.as_ptr().cast::<VarLenAscii>().as_ref() }.unwrap();
+            match s.as_ptr().is_null() {
+                true => json.push_str("null"),
+                false => write_string(json, s.as_str()),
+            }
+        }
+        TypeDescriptor::VarLenUnicode => {
+            let s = unsafe { slice.as_ptr().cast::<VarLenUnicode>().as_ref() }.unwrap();
+            match s.as_ptr().is_null() {
+                true => json.push_str("null"),
+                false => write_string(json, s.as_str()),
+            }
+        }
+        TypeDescriptor::Reference(_) => {
+            // The raw bytes in hex, as they only make sense within the file.
+            json.push('"');
+            for b in &slice[..dtype.size()] {
+                write!(json, "{b:02x}").unwrap();
+            }
+            json.push('"');
+        }
+    }
+}
+
+fn write_array(json: &mut String, dtype: &TypeDescriptor, data: &[u8], len: usize) {
+    json.push('[');
+    for i in 0..len {
+        if i > 0 {
+            json.push(',');
+        }
+        write_item(json, dtype, &data[i * dtype.size()..]);
+    }
+    json.push(']');
+}
+
+pub struct Hdf5ReadJsonBindData {
+    data: Hdf5FileData,
+}
+
+pub struct Hdf5ReadJsonInitData {
+    /// The index of the next vector to emit.
+    chunk: AtomicUsize,
+}
+
+/// `read_hdf5_json(path, dataset)`: every item of a dataset as a JSON document.
+pub(super) struct Hdf5ReadJson;
+
+impl VTab for Hdf5ReadJson {
+    type InitData = Hdf5ReadJsonInitData;
+    type BindData = Hdf5ReadJsonBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let dataset = bind.get_parameter(1).to_string();
+        let mut options = Hdf5ReadOptions::from_bind(bind)?;
+        // Nested compounds are objects, never flattened.
+        options.max_struct_fields = usize::MAX;
+        let max_elements = bind
+            .get_named_parameter("max_elements")
+            .map(|max| usize::try_from(max.to_int64()))
+            .transpose()
+            .map_err(|_| "max_elements must not be negative")?
+            .unwrap_or(DEFAULT_MAX_ELEMENTS);
+        let file = open_path(&path)?;
+        // Check the size before anything is read.
+        let target = open_dataset(&file, &dataset, &path)?;
+        let len = if options.hyperslab.is_empty() {
+            dataset_len(&target)?
+        } else {
+            options.hyperslab.resolve(&target.shape())?.len()
+        };
+        if len > max_elements {
+            return Err(Hdf5ReadError::TooLarge(format!(
+                "'{}' of '{path}' has {len} items, more than max_elements {max_elements}; \
+                 select fewer with \"offset\" and count",
+                target.name()
+            ))
+            .into());
+        }
+        let data = Hdf5FileData::read(&file, &path, Some(&dataset), &options, false)?;
+        bind.add_result_column("json", LogicalTypeId::Varchar.into());
+        bind.set_cardinality(data.len as _, true);
+        Ok(Hdf5ReadJsonBindData { data })
+    }
+
+    fn init(_: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Ok(Hdf5ReadJsonInitData {
+            chunk: AtomicUsize::new(0),
+        })
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let file = &func.get_bind_data().data;
+        let Hdf5FileData { dtype, len, .. } = file;
+        let data = file.items()?;
+        let capacity = unsafe { ffi::duckdb_vector_size() } as usize;
+        let chunk = func.get_init_data().chunk.fetch_add(1, Ordering::Relaxed);
+        let start = (chunk * capacity).min(*len);
+        let count = (len - start).min(capacity);
+        let vector = output.flat_vector(0);
+        let mut json = String::new();
+        for row in 0..count {
+            json.clear();
+            write_item(&mut json, dtype, &data[(start + row) * dtype.size()..]);
+            vector.insert(row, json.as_str());
+        }
+        output.set_len(count);
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+
+    fn named_parameters() -> Option<Vec<(String, LogicalTypeHandle)>> {
+        let indices = || LogicalTypeHandle::list(&LogicalTypeId::UBigint.into());
+        Some(vec![
+            ("offset".to_string(), indices()),
+            ("count".to_string(), indices()),
+            ("stride".to_string(), indices()),
+            ("max_elements".to_string(), LogicalTypeId::Bigint.into()),
+        ])
+    }
+}
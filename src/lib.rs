@@ -1,31 +1,141 @@
+use attributes::{
+    parse_map, parse_names, AttributeValue, Hdf5AllAttributes, Hdf5Attributes, Hdf5GroupAttributes,
+};
+use batch::batch;
+use blob::Hdf5ReadBlob;
+use chunks::{Hdf5Chunk, Hdf5Chunks};
+use diff::Hdf5Diff;
+use driver::{open_direct, open_family, open_split, Driver};
 use duckdb::{
-    core::{DataChunkHandle, Inserter, LogicalTypeHandle, LogicalTypeId},
+    core::{DataChunkHandle, FlatVector, Inserter, ListVector, LogicalTypeHandle, LogicalTypeId},
     ffi,
-    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab, Value},
     Connection, Result,
 };
 use duckdb_loadable_macros::duckdb_entrypoint_c_api;
-use hdf5::types::{
-    CompoundType, FloatSize, IntSize, TypeDescriptor, VarLenArray, VarLenAscii, VarLenUnicode,
+use eos::Hdf5EosStructure;
+pub use error::Hdf5ReadError;
+use hdf5::{
+    datatype::ByteOrder,
+    filters::Filter,
+    plist::dataset_create::Layout,
+    types::{
+        CompoundField, CompoundType, FloatSize, IntSize, Reference, TypeDescriptor, VarLenArray,
+        VarLenAscii,
+    },
+};
+use head::{Hdf5Head, Hdf5Tail};
+use hint::{parse_schema_hint, ColumnHint, Mark};
+use hyperslab::{mask_space, parse_indices, read_masked, read_selected_with, Hyperslab, Selection};
+use info::Hdf5DatasetInfo;
+use json::Hdf5ReadJson;
+use long::Hdf5ReadLong;
+use meta::Hdf5ReadMeta;
+use mmap::{map_dataset, Bytes};
+use nexus::Hdf5ReadNexus;
+use packet_table::Hdf5ReadPacketTable;
+use partition::{expand_path, is_hive_partitioned, Partition};
+use record::Hdf5ReadRecord;
+use references::{
+    has_reference_fields, is_object_reference, reference_array_type, ReferenceFields, References,
+    Targets,
 };
+use regions::{is_region_reference, Regions};
+use scalar::{Hdf5Dtype, Hdf5ReadBase64, Hdf5Readlink, Hdf5Version};
+use stats::Hdf5ColumnStats;
 use std::{
     borrow::Cow,
+    collections::HashSet,
     error::Error,
-    ops::Deref,
-    sync::atomic::{AtomicUsize, Ordering},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
 };
+use stream::{has_vlen, VlenBatch, VlenItems, VlenStream};
+
+// Explicit paths, as src/wasm_lib.rs includes this file as a module.
+#[path = "attributes.rs"]
+mod attributes;
+#[path = "batch.rs"]
+mod batch;
+#[path = "blob.rs"]
+mod blob;
+#[path = "chunks.rs"]
+mod chunks;
+#[path = "datatype.rs"]
+mod datatype;
+#[path = "diff.rs"]
+mod diff;
+#[path = "driver.rs"]
+mod driver;
+#[path = "eos.rs"]
+mod eos;
+#[path = "error.rs"]
+mod error;
+#[path = "head.rs"]
+mod head;
+#[path = "hint.rs"]
+mod hint;
+#[path = "hyperslab.rs"]
+mod hyperslab;
+#[path = "info.rs"]
+mod info;
+#[path = "json.rs"]
+mod json;
+#[path = "long.rs"]
+mod long;
+#[path = "meta.rs"]
+mod meta;
+#[path = "mmap.rs"]
+mod mmap;
+#[path = "nexus.rs"]
+mod nexus;
+#[path = "packet_table.rs"]
+mod packet_table;
+#[path = "partition.rs"]
+mod partition;
+#[path = "record.rs"]
+mod record;
+#[path = "references.rs"]
+mod references;
+#[path = "regions.rs"]
+mod regions;
+#[path = "scalar.rs"]
+mod scalar;
+#[path = "stats.rs"]
+mod stats;
+#[path = "stream.rs"]
+mod stream;
 
 pub trait ReadRawBytes {
     fn read_raw_bytes(&self, dtype: &TypeDescriptor) -> hdf5::Result<Vec<u8>>;
 }
 
+/// The number of items of `dataset`. A null dataspace has none, though it
+/// has no dimensions, like a scalar one.
+fn dataset_len(dataset: &hdf5::Dataset) -> hdf5::Result<usize> {
+    Ok(if dataset.space()?.is_null() {
+        0
+    } else {
+        dataset.size()
+    })
+}
+
 impl ReadRawBytes for hdf5::Dataset {
     fn read_raw_bytes(&self, dtype: &TypeDescriptor) -> hdf5::Result<Vec<u8>> {
-        let len = self.size();
+        let len = dataset_len(self)?;
+        if len == 0 {
+            return Ok(vec![]);
+        }
         let item_size = dtype.size();
         let mut buffer = Vec::with_capacity(len * item_size);
         // Convert again to fit the current native endian.
-        let native_dtype = hdf5::Datatype::from_descriptor(dtype)?;
+        let native_dtype = datatype::memory_type(dtype, &self.dtype()?)?;
         hdf5::h5call!(hdf5_sys::h5d::H5Dread(
             self.id(),
             native_dtype.id(),
@@ -41,240 +151,3242 @@ impl ReadRawBytes for hdf5::Dataset {
     }
 }
 
-struct Hdf5ReadBindData {
-    dtype: TypeDescriptor,
-    data: Vec<u8>,
+impl ReadRawBytes for hdf5::Attribute {
+    fn read_raw_bytes(&self, dtype: &TypeDescriptor) -> hdf5::Result<Vec<u8>> {
+        let len = self.size();
+        let item_size = dtype.size();
+        let mut buffer = Vec::with_capacity(len * item_size);
+        let native_dtype = datatype::from_descriptor(dtype)?;
+        hdf5::h5call!(hdf5_sys::h5a::H5Aread(
+            self.id(),
+            native_dtype.id(),
+            buffer.spare_capacity_mut().as_mut_ptr() as *mut _
+        ))?;
+        unsafe {
+            buffer.set_len(len * item_size);
+        }
+        Ok(buffer)
+    }
 }
 
-const RESULT_COLNAME: Cow<str> = Cow::Borrowed("result");
+/// How the bytes of numeric elements are interpreted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Endian {
+    /// Convert from the declared byte order of the file.
+    #[default]
+    Native,
+    /// Interpret the stored bytes as little-endian.
+    Little,
+    /// Interpret the stored bytes as big-endian.
+    Big,
+    /// Interpret the stored bytes as-is, in the native byte order.
+    Source,
+}
 
-fn iter_dtype(dtype: &TypeDescriptor) -> Vec<(Cow<'static, str>, LogicalTypeHandle)> {
-    match dtype {
-        TypeDescriptor::Integer(size) => {
-            let ty = match size {
-                IntSize::U1 => LogicalTypeId::Tinyint,
-                IntSize::U2 => LogicalTypeId::Smallint,
-                IntSize::U4 => LogicalTypeId::Integer,
-                IntSize::U8 => LogicalTypeId::Bigint,
-            };
-            vec![(RESULT_COLNAME, ty.into())]
-        }
-        TypeDescriptor::Unsigned(size) => {
-            let ty = match size {
-                IntSize::U1 => LogicalTypeId::UTinyint,
-                IntSize::U2 => LogicalTypeId::USmallint,
-                IntSize::U4 => LogicalTypeId::UInteger,
-                IntSize::U8 => LogicalTypeId::UBigint,
-            };
-            vec![(RESULT_COLNAME, ty.into())]
+impl Endian {
+    /// Whether the natively-converted bytes of a type stored in `order` need
+    /// to be swapped to get the requested interpretation.
+    fn needs_swap(self, order: ByteOrder) -> bool {
+        let target = match self {
+            Self::Native => return false,
+            Self::Little => ByteOrder::LittleEndian,
+            Self::Big => ByteOrder::BigEndian,
+            Self::Source if cfg!(target_endian = "little") => ByteOrder::LittleEndian,
+            Self::Source => ByteOrder::BigEndian,
+        };
+        matches!(order, ByteOrder::LittleEndian | ByteOrder::BigEndian) && order != target
+    }
+}
+
+impl FromStr for Endian {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "native" => Ok(Self::Native),
+            "little" => Ok(Self::Little),
+            "big" => Ok(Self::Big),
+            "source" => Ok(Self::Source),
+            _ => Err(format!(
+                "invalid endian '{s}', expected 'native', 'little', 'big' or 'source'"
+            )),
         }
-        TypeDescriptor::Float(size) => {
-            let ty = match size {
-                FloatSize::U4 => LogicalTypeId::Float,
-                FloatSize::U8 => LogicalTypeId::Double,
-            };
-            vec![(RESULT_COLNAME, ty.into())]
+    }
+}
+
+/// How NaN and infinite floats are read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum FloatSpecial {
+    /// Keep them as DuckDB floats.
+    #[default]
+    Keep,
+    /// Read them as NULL.
+    Null,
+    /// Fail the read.
+    Error,
+}
+
+impl FromStr for FloatSpecial {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "keep" => Ok(Self::Keep),
+            "null" => Ok(Self::Null),
+            "error" => Ok(Self::Error),
+            _ => Err(format!(
+                "invalid float_special '{s}', expected 'keep', 'null' or 'error'"
+            )),
+        }
+    }
+}
+
+/// How compound columns are read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum CompoundAs {
+    /// A `STRUCT` with a member per field.
+    #[default]
+    Struct,
+    /// A `MAP` of the names of the fields to their values.
+    Map,
+}
+
+impl FromStr for CompoundAs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "struct" => Ok(Self::Struct),
+            "map" => Ok(Self::Map),
+            _ => Err(format!(
+                "invalid compound_as '{s}', expected 'struct' or 'map'"
+            )),
+        }
+    }
+}
+
+/// How unsigned 64-bit integer columns are read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum UbigintAs {
+    /// A `UBIGINT`.
+    #[default]
+    Ubigint,
+    /// A `DOUBLE`, losing precision beyond 2^53.
+    Double,
+    /// A `VARCHAR` of the decimal digits, for consumers without `UBIGINT`.
+    Varchar,
+}
+
+impl FromStr for UbigintAs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ubigint" => Ok(Self::Ubigint),
+            "double" => Ok(Self::Double),
+            "varchar" => Ok(Self::Varchar),
+            _ => Err(format!(
+                "invalid ubigint_as '{s}', expected 'ubigint', 'double' or 'varchar'"
+            )),
+        }
+    }
+}
+
+/// The order of the items of a dataset of many dimensions as rows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Order {
+    /// Row-major, the last dimension varying fastest, as stored by HDF5.
+    #[default]
+    C,
+    /// Column-major, the first dimension varying fastest, as in Fortran.
+    Fortran,
+}
+
+impl FromStr for Order {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "C" => Ok(Self::C),
+            "F" => Ok(Self::Fortran),
+            _ => Err(format!("invalid order '{s}', expected 'C' or 'F'")),
+        }
+    }
+}
+
+/// Reorder the row-major items of `size` bytes of an array of `shape` to
+/// column-major.
+fn to_column_major(data: &[u8], shape: &[usize], size: usize) -> Vec<u8> {
+    let mut strides = vec![1; shape.len()];
+    for i in (1..shape.len()).rev() {
+        strides[i - 1] = strides[i] * shape[i];
+    }
+    let mut out = Vec::with_capacity(data.len());
+    for row in 0..data.len() / size {
+        let (mut rest, mut item) = (row, 0);
+        for (dim, stride) in shape.iter().zip(&strides) {
+            item += rest % dim * stride;
+            rest /= dim;
+        }
+        out.extend_from_slice(&data[item * size..][..size]);
+    }
+    out
+}
+
+/// Reverse the bytes of every numeric element in a natively-read item.
+fn swap_bytes(dtype: &TypeDescriptor, slice: &mut [u8]) {
+    match dtype {
+        TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) | TypeDescriptor::Float(_) => {
+            slice[..dtype.size()].reverse()
         }
-        TypeDescriptor::Boolean => vec![(RESULT_COLNAME, LogicalTypeId::Boolean.into())],
-        TypeDescriptor::Enum(e) => iter_dtype(&e.base_type()),
+        TypeDescriptor::Enum(e) => swap_bytes(&e.base_type(), slice),
         TypeDescriptor::Compound(c) => {
-            let mut res = vec![];
             for f in &c.fields {
-                let (names, types) = iter_dtype(&f.ty)
-                    .into_iter()
-                    .unzip::<Cow<'static, str>, LogicalTypeHandle, Vec<_>, Vec<_>>();
-                let ty = if types.len() > 1 {
-                    let types = types
-                        .into_iter()
-                        .zip(&names)
-                        .map(|(ty, name)| (name.deref(), ty))
-                        .collect::<Vec<_>>();
-                    LogicalTypeHandle::struct_type(types.as_slice())
-                } else {
-                    types.into_iter().next().unwrap()
-                };
-                res.push((Cow::Owned(f.name.clone()), ty))
+                swap_bytes(&f.ty, &mut slice[f.offset..]);
             }
-            res
         }
         TypeDescriptor::FixedArray(ty, len) => {
-            let inner = &iter_dtype(ty)[0].1;
-            let ty = LogicalTypeHandle::array(inner, *len as _);
-            vec![(RESULT_COLNAME, ty)]
+            for i in 0..*len {
+                swap_bytes(ty, &mut slice[i * ty.size()..]);
+            }
         }
         TypeDescriptor::VarLenArray(ty) => {
-            let inner = &iter_dtype(ty)[0].1;
-            let ty = LogicalTypeHandle::list(inner);
-            vec![(RESULT_COLNAME, ty)]
+            let array = unsafe { slice.as_ptr().cast::<VarLenArray<u8>>().as_ref() }.unwrap();
+            let item_size = ty.size();
+            if array.is_empty() {
+                return;
+            }
+            // The elements live in a buffer allocated by HDF5 for this read.
+            let data = unsafe {
+                std::slice::from_raw_parts_mut(array.as_ptr() as *mut u8, array.len() * item_size)
+            };
+            for item in data.chunks_exact_mut(item_size) {
+                swap_bytes(ty, item);
+            }
         }
-        TypeDescriptor::FixedAscii(_)
+        TypeDescriptor::Boolean
+        | TypeDescriptor::FixedAscii(_)
         | TypeDescriptor::FixedUnicode(_)
         | TypeDescriptor::VarLenAscii
-        | TypeDescriptor::VarLenUnicode => {
-            vec![(RESULT_COLNAME, LogicalTypeId::Varchar.into())]
+        | TypeDescriptor::VarLenUnicode
+        | TypeDescriptor::Reference(_) => {}
+    }
+}
+
+/// Nested compounds with more fields are flattened into columns by default.
+const DEFAULT_MAX_STRUCT_FIELDS: usize = 100;
+/// Files larger than this are never read whole into memory with `image`.
+const MAX_IMAGE_SIZE: u64 = 1 << 30;
+
+/// Options of `read_hdf5`, parsed from its named parameters.
+#[derive(Debug)]
+struct Hdf5ReadOptions {
+    endian: Endian,
+    hive_partitioning: Option<bool>,
+    apply_scale: bool,
+    max_struct_fields: usize,
+    all_double: bool,
+    /// Columns read as another type, converted by HDF5 without loss.
+    cast_to: Vec<(String, TypeDescriptor)>,
+    hyperslab: Hyperslab,
+    image: bool,
+    /// Attributes of the dataset added as constant columns.
+    attrs: Vec<String>,
+    attrs_optional: bool,
+    /// Merge the compound fields of the files by name.
+    union_by_name: bool,
+    /// Read the regions referenced by region references.
+    deref_regions: bool,
+    /// Read the datasets referenced by object references.
+    deref: bool,
+    /// Open files for SWMR reads, while another process may write to them.
+    swmr: bool,
+    /// A dataset of the same shape, selecting the items where it is nonzero.
+    mask_dataset: Option<String>,
+    /// The compound fields to read, by name.
+    columns: Option<Vec<String>>,
+    /// The names of the dataset's columns, replacing the field names.
+    column_names: Option<Vec<String>>,
+    /// The size of the chunk cache of chunked datasets, in bytes.
+    chunk_cache_bytes: Option<usize>,
+    /// The number of samples along the outer axis grouped into each row.
+    batch: Option<usize>,
+    driver: Driver,
+    /// The size of the members of a family file, in bytes.
+    member_size: Option<usize>,
+    /// The alignment of the objects of the file, in bytes.
+    alignment: Option<usize>,
+    /// Read the next vector of a streamed dataset in the background.
+    prefetch: bool,
+    /// Map contiguous datasets from the file instead of reading them.
+    mmap: bool,
+    /// The number of items of the arrays and lists of each column to keep.
+    preview: Option<usize>,
+    /// Read integer columns as arrays of their bits.
+    bits_to_list: bool,
+    /// How to read some columns, merged with the options above.
+    schema_hint: Vec<ColumnHint>,
+    float_special: FloatSpecial,
+    /// The order of the items of a dataset of many dimensions.
+    order: Order,
+    /// Add the bytes of each compound item as a `__raw` column.
+    raw_compound: bool,
+    /// The number of bytes of each string to keep.
+    max_string_len: Option<usize>,
+    /// Add the position of each row as an `__index` column.
+    with_index: bool,
+    /// Emit the rows from the last to the first.
+    reverse: bool,
+    /// Read the values of `_FillValue` and `missing_value` as NULL.
+    nulls_from_fill: bool,
+    /// The time the scan may take, from its start.
+    timeout: Option<Duration>,
+    /// Drop the axes of size 1 of the dataset.
+    squeeze: bool,
+    /// The offset of the HDF5 file in each file, after a user block or other
+    /// data.
+    user_block_offset: Option<u64>,
+    compound_as: CompoundAs,
+    ubigint_as: UbigintAs,
+}
+
+impl Hdf5ReadOptions {
+    fn from_bind(bind: &BindInfo) -> Result<Self, Box<dyn Error>> {
+        let flag = |name: &str| {
+            bind.get_named_parameter(name)
+                .map(|value| value.to_string().eq_ignore_ascii_case("true"))
+        };
+        let indices = |name: &str| {
+            bind.get_named_parameter(name)
+                .map(|value| parse_indices(name, &value.to_string()))
+                .transpose()
+        };
+        let preview_k = bind
+            .get_named_parameter("preview_k")
+            .map(|k| {
+                usize::try_from(k.to_int64())
+                    .ok()
+                    .filter(|k| *k > 0)
+                    .ok_or("preview_k must be positive")
+            })
+            .transpose()?;
+        let mut options = Self {
+            endian: bind
+                .get_named_parameter("endian")
+                .map(|endian| endian.to_string().parse())
+                .transpose()?
+                .unwrap_or_default(),
+            hive_partitioning: flag("hive_partitioning"),
+            apply_scale: flag("apply_scale").unwrap_or_default(),
+            max_struct_fields: bind
+                .get_named_parameter("max_struct_fields")
+                .map(|max| usize::try_from(max.to_int64()))
+                .transpose()
+                .map_err(|_| "max_struct_fields must not be negative")?
+                .unwrap_or(DEFAULT_MAX_STRUCT_FIELDS),
+            all_double: flag("all_double").unwrap_or_default(),
+            cast_to: bind
+                .get_named_parameter("cast_to")
+                .map(|cast_to| parse_map("cast_to", &cast_to.to_string()))
+                .transpose()?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(column, ty)| Ok((column, parse_cast_type(&ty)?)))
+                .collect::<Result<_, Box<dyn Error>>>()?,
+            hyperslab: Hyperslab {
+                offset: indices("offset")?,
+                count: indices("count")?,
+                stride: indices("stride")?,
+            },
+            image: flag("image").unwrap_or_default(),
+            attrs: bind
+                .get_named_parameter("attrs")
+                .map(|attrs| parse_names("attrs", &attrs.to_string()))
+                .transpose()?
+                .unwrap_or_default(),
+            attrs_optional: flag("attrs_optional").unwrap_or_default(),
+            union_by_name: flag("union_by_name").unwrap_or_default(),
+            deref_regions: flag("deref_regions").unwrap_or_default(),
+            deref: flag("deref").unwrap_or_default(),
+            swmr: flag("swmr").unwrap_or_default(),
+            columns: bind
+                .get_named_parameter("columns")
+                .map(|columns| parse_names("columns", &columns.to_string()))
+                .transpose()?,
+            column_names: bind
+                .get_named_parameter("column_names")
+                .map(|names| parse_names("column_names", &names.to_string()))
+                .transpose()?,
+            mask_dataset: bind
+                .get_named_parameter("mask_dataset")
+                .map(|mask| mask.to_string()),
+            chunk_cache_bytes: bind
+                .get_named_parameter("chunk_cache_bytes")
+                .map(|bytes| {
+                    usize::try_from(bytes.to_int64())
+                        .ok()
+                        .filter(|bytes| *bytes > 0)
+                        .ok_or("chunk_cache_bytes must be positive")
+                })
+                .transpose()?,
+            batch: bind
+                .get_named_parameter("batch")
+                .map(|batch| {
+                    usize::try_from(batch.to_int64())
+                        .ok()
+                        .filter(|batch| *batch > 0)
+                        .ok_or("batch must be positive")
+                })
+                .transpose()?,
+            driver: bind
+                .get_named_parameter("driver")
+                .map(|driver| driver.to_string().parse())
+                .transpose()?
+                .unwrap_or_default(),
+            member_size: bind
+                .get_named_parameter("member_size")
+                .map(|size| {
+                    usize::try_from(size.to_int64())
+                        .ok()
+                        .filter(|size| *size > 0)
+                        .ok_or("member_size must be positive")
+                })
+                .transpose()?,
+            alignment: bind
+                .get_named_parameter("alignment")
+                .map(|alignment| {
+                    usize::try_from(alignment.to_int64())
+                        .ok()
+                        .filter(|alignment| *alignment > 0)
+                        .ok_or("alignment must be positive")
+                })
+                .transpose()?,
+            prefetch: flag("prefetch").unwrap_or_default(),
+            mmap: flag("mmap").unwrap_or_default(),
+            preview: flag("preview")
+                .unwrap_or_default()
+                .then(|| preview_k.unwrap_or(1)),
+            bits_to_list: flag("bits_to_list").unwrap_or_default(),
+            schema_hint: bind
+                .get_named_parameter("schema_hint")
+                .map(|hint| parse_schema_hint(&hint.to_string()))
+                .transpose()?
+                .unwrap_or_default(),
+            float_special: bind
+                .get_named_parameter("float_special")
+                .map(|special| special.to_string().parse())
+                .transpose()?
+                .unwrap_or_default(),
+            order: bind
+                .get_named_parameter("order")
+                .map(|order| order.to_string().parse())
+                .transpose()?
+                .unwrap_or_default(),
+            raw_compound: flag("raw_compound").unwrap_or_default(),
+            max_string_len: bind
+                .get_named_parameter("max_string_len")
+                .map(|len| {
+                    usize::try_from(len.to_int64())
+                        .ok()
+                        .filter(|len| *len > 0)
+                        .ok_or("max_string_len must be positive")
+                })
+                .transpose()?,
+            with_index: flag("with_index").unwrap_or_default(),
+            reverse: flag("reverse").unwrap_or_default(),
+            nulls_from_fill: flag("nulls_from_fill").unwrap_or_default(),
+            timeout: bind
+                .get_named_parameter("timeout_ms")
+                .map(|ms| {
+                    u64::try_from(ms.to_int64())
+                        .ok()
+                        .filter(|ms| *ms > 0)
+                        .map(Duration::from_millis)
+                        .ok_or("timeout_ms must be positive")
+                })
+                .transpose()?,
+            squeeze: flag("squeeze").unwrap_or_default(),
+            user_block_offset: bind
+                .get_named_parameter("user_block_offset")
+                .map(|offset| {
+                    u64::try_from(offset.to_int64())
+                        .map_err(|_| "user_block_offset must not be negative")
+                })
+                .transpose()?,
+            compound_as: bind
+                .get_named_parameter("compound_as")
+                .map(|compound_as| compound_as.to_string().parse())
+                .transpose()?
+                .unwrap_or_default(),
+            ubigint_as: bind
+                .get_named_parameter("ubigint_as")
+                .map(|ubigint_as| ubigint_as.to_string().parse())
+                .transpose()?
+                .unwrap_or_default(),
+        };
+        // The types of the hint are casts, unless `cast_to` has its own.
+        for hint in &options.schema_hint {
+            if let Some(dtype) = &hint.dtype {
+                if !options.cast_to.iter().any(|(c, _)| *c == hint.column) {
+                    options.cast_to.push((hint.column.clone(), dtype.clone()));
+                }
+            }
+        }
+        // The bytes would be swapped after HDF5 converted them to doubles.
+        if options.all_double && options.endian != Endian::Native {
+            return Err("all_double cannot be combined with endian".into());
+        }
+        if !options.cast_to.is_empty() && (options.all_double || options.endian != Endian::Native) {
+            return Err("cast_to cannot be combined with all_double or endian".into());
+        }
+        if options.bits_to_list && options.apply_scale {
+            return Err("bits_to_list cannot be combined with apply_scale".into());
         }
-        TypeDescriptor::Reference(_) => vec![(RESULT_COLNAME, LogicalTypeId::Blob.into())],
+        // The integers would be doubles, bits or lists instead.
+        if options.ubigint_as != UbigintAs::Ubigint
+            && (options.all_double
+                || options.apply_scale
+                || options.bits_to_list
+                || options.batch.is_some())
+        {
+            return Err(
+                "ubigint_as cannot be combined with all_double, apply_scale, bits_to_list \
+                 or batch"
+                    .into(),
+            );
+        }
+        if preview_k.is_some() && options.preview.is_none() {
+            return Err("preview_k requires preview".into());
+        }
+        if options.mask_dataset.is_some() && !options.hyperslab.is_empty() {
+            return Err("mask_dataset cannot be combined with offset, count or stride".into());
+        }
+        if options.batch.is_some()
+            && (options.apply_scale || options.deref_regions || options.mask_dataset.is_some())
+        {
+            return Err(
+                "batch cannot be combined with apply_scale, deref_regions or mask_dataset".into(),
+            );
+        }
+        // The items must keep the layout of the file.
+        if options.raw_compound
+            && (options.all_double
+                || !options.cast_to.is_empty()
+                || options.columns.is_some()
+                || options.batch.is_some()
+                || options.max_string_len.is_some())
+        {
+            return Err(
+                "raw_compound cannot be combined with all_double, cast_to, columns, \
+                        batch or max_string_len"
+                    .into(),
+            );
+        }
+        if options.nulls_from_fill && options.batch.is_some() {
+            return Err("nulls_from_fill cannot be combined with batch".into());
+        }
+        if options.order == Order::Fortran
+            && (options.batch.is_some() || options.mask_dataset.is_some())
+        {
+            return Err("order = 'F' cannot be combined with batch or mask_dataset".into());
+        }
+        if options.member_size.is_some() && options.driver != Driver::Family {
+            return Err("member_size requires driver = 'family'".into());
+        }
+        if options.driver != Driver::Sec2 && (options.swmr || options.image) {
+            return Err("driver cannot be combined with swmr or image".into());
+        }
+        if options.swmr && options.image {
+            return Err("swmr cannot be combined with image".into());
+        }
+        if options.alignment.is_some()
+            && (options.swmr
+                || options.image
+                || matches!(options.driver, Driver::Family | Driver::Split))
+        {
+            return Err(
+                "alignment cannot be combined with swmr, image, or driver = 'family' or 'split'"
+                    .into(),
+            );
+        }
+        if options.user_block_offset.is_some()
+            && (options.swmr || options.alignment.is_some() || options.driver != Driver::Sec2)
+        {
+            return Err(
+                "user_block_offset cannot be combined with swmr, alignment, \
+                        or driver = 'direct', 'family' or 'split'"
+                    .into(),
+            );
+        }
+        if options.deref_regions
+            && (options.endian != Endian::Native || !options.hyperslab.is_empty())
+        {
+            return Err(
+                "deref_regions cannot be combined with endian, offset, count or stride".into(),
+            );
+        }
+        if options.deref
+            && (options.endian != Endian::Native
+                || !options.hyperslab.is_empty()
+                || options.batch.is_some()
+                || options.mask_dataset.is_some())
+        {
+            return Err(
+                "deref cannot be combined with endian, offset, count, stride, batch \
+                        or mask_dataset"
+                    .into(),
+            );
+        }
+        // The referenced items are read as they are, without the options
+        // applied to the items of the dataset.
+        if (options.deref || options.deref_regions)
+            && (options.columns.is_some()
+                || !options.cast_to.is_empty()
+                || options.apply_scale
+                || options.max_string_len.is_some()
+                || options.nulls_from_fill
+                || options.squeeze
+                || options.mmap
+                || options.order == Order::Fortran)
+        {
+            let name = if options.deref {
+                "deref"
+            } else {
+                "deref_regions"
+            };
+            return Err(format!(
+                "{name} cannot be combined with columns, cast_to, the types of schema_hint, \
+                 apply_scale, max_string_len, nulls_from_fill, squeeze, mmap or order = 'F'"
+            )
+            .into());
+        }
+        Ok(options)
     }
 }
 
-macro_rules! fill_vec {
-    ($output:expr, $idx:expr, $slice:expr, $t:ty) => {{
-        let mut vec = $output.flat_vector($idx);
-        vec.as_mut_slice::<$t>()[0] = unsafe { $slice.as_ptr().cast::<$t>().read_unaligned() };
-    }};
+/// CF-style packing, unpacked as `value * factor + offset`.
+#[derive(Debug, Clone, Copy)]
+struct Scale {
+    factor: f64,
+    offset: f64,
+}
+
+impl Scale {
+    /// Read the `scale_factor` and `add_offset` attributes of a dataset.
+    fn read(dataset: &hdf5::Dataset) -> hdf5::Result<Self> {
+        let attr = |name: &str, default: f64| -> hdf5::Result<f64> {
+            if dataset.attr_names()?.iter().any(|n| n == name) {
+                Ok(dataset
+                    .attr(name)?
+                    .read_raw::<f64>()?
+                    .first()
+                    .copied()
+                    .unwrap_or(default))
+            } else {
+                Ok(default)
+            }
+        };
+        Ok(Self {
+            factor: attr("scale_factor", 1.0)?,
+            offset: attr("add_offset", 0.0)?,
+        })
+    }
+}
+
+/// The type of a DuckDB type name of `cast_to`.
+fn parse_cast_type(name: &str) -> Result<TypeDescriptor, Box<dyn Error>> {
+    Ok(match name.to_ascii_uppercase().as_str() {
+        "TINYINT" | "INT1" => TypeDescriptor::Integer(IntSize::U1),
+        "SMALLINT" | "INT2" => TypeDescriptor::Integer(IntSize::U2),
+        "INTEGER" | "INT" | "INT4" => TypeDescriptor::Integer(IntSize::U4),
+        "BIGINT" | "INT8" => TypeDescriptor::Integer(IntSize::U8),
+        "UTINYINT" => TypeDescriptor::Unsigned(IntSize::U1),
+        "USMALLINT" => TypeDescriptor::Unsigned(IntSize::U2),
+        "UINTEGER" => TypeDescriptor::Unsigned(IntSize::U4),
+        "UBIGINT" => TypeDescriptor::Unsigned(IntSize::U8),
+        "FLOAT" | "REAL" | "FLOAT4" => TypeDescriptor::Float(FloatSize::U4),
+        "DOUBLE" | "FLOAT8" => TypeDescriptor::Float(FloatSize::U8),
+        _ => return Err(format!("unsupported type in cast_to: {name}").into()),
+    })
+}
+
+/// Whether every item of `from` is exactly an item of `to`: integers widen
+/// to integers that hold their range, and to floats of enough precision.
+fn is_lossless_cast(from: &TypeDescriptor, to: &TypeDescriptor) -> bool {
+    match (from, to) {
+        (TypeDescriptor::Integer(a), TypeDescriptor::Integer(b))
+        | (TypeDescriptor::Unsigned(a), TypeDescriptor::Unsigned(b)) => *a as usize <= *b as usize,
+        (TypeDescriptor::Unsigned(a), TypeDescriptor::Integer(b)) => (*a as usize) < *b as usize,
+        (TypeDescriptor::Integer(a) | TypeDescriptor::Unsigned(a), TypeDescriptor::Float(b)) => {
+            (*a as usize) < *b as usize
+        }
+        (TypeDescriptor::Float(a), TypeDescriptor::Float(b)) => *a as usize <= *b as usize,
+        _ => false,
+    }
+}
+
+/// The descriptor with the columns of `cast_to` replaced by their types,
+/// letting HDF5 convert the items while reading.
+fn cast_columns(
+    dtype: &TypeDescriptor,
+    cast_to: &[(String, TypeDescriptor)],
+    name: &str,
+) -> Result<TypeDescriptor, Box<dyn Error>> {
+    let cast = |column: &str, ty: &TypeDescriptor| match cast_to.iter().find(|(c, _)| c == column) {
+        Some((_, to)) if is_lossless_cast(ty, to) => Ok(to.clone()),
+        Some((_, to)) => Err(format!(
+            "cannot cast column '{column}' of '{name}' from {} to {} without loss",
+            dtype_name(ty),
+            dtype_name(to)
+        )),
+        None => Ok(ty.clone()),
+    };
+    let columns: Vec<&str> = match dtype {
+        TypeDescriptor::Compound(c) => c.fields.iter().map(|f| f.name.as_str()).collect(),
+        _ => vec![&RESULT_COLNAME],
+    };
+    if let Some((column, _)) = cast_to.iter().find(|(c, _)| !columns.contains(&c.as_str())) {
+        return Err(format!("cast_to column '{column}' not found in '{name}'").into());
+    }
+    let TypeDescriptor::Compound(c) = dtype else {
+        return Ok(cast(&RESULT_COLNAME, dtype)?);
+    };
+    let mut offset = 0;
+    let mut fields = vec![];
+    for f in &c.fields {
+        let field = CompoundField::new(&f.name, cast(&f.name, &f.ty)?, offset, f.index);
+        offset += field.ty.size();
+        fields.push(field);
+    }
+    Ok(TypeDescriptor::Compound(CompoundType {
+        fields,
+        size: offset,
+    }))
 }
 
-fn fill(dtype: &TypeDescriptor, slice: &[u8], output: &mut DataChunkHandle, idx: usize) {
+/// The descriptor with every fixed-length string longer than `len` bytes cut
+/// to it, letting HDF5 truncate the items while reading.
+fn shorten_strings(dtype: &TypeDescriptor, len: usize) -> TypeDescriptor {
     match dtype {
-        TypeDescriptor::Integer(IntSize::U1) => fill_vec!(output, idx, slice, i8),
-        TypeDescriptor::Integer(IntSize::U2) => fill_vec!(output, idx, slice, i16),
-        TypeDescriptor::Integer(IntSize::U4) => fill_vec!(output, idx, slice, i32),
-        TypeDescriptor::Integer(IntSize::U8) => fill_vec!(output, idx, slice, i64),
-        TypeDescriptor::Unsigned(IntSize::U1) => fill_vec!(output, idx, slice, u8),
-        TypeDescriptor::Unsigned(IntSize::U2) => fill_vec!(output, idx, slice, u16),
-        TypeDescriptor::Unsigned(IntSize::U4) => fill_vec!(output, idx, slice, u32),
-        TypeDescriptor::Unsigned(IntSize::U8) => fill_vec!(output, idx, slice, u64),
-        TypeDescriptor::Float(FloatSize::U4) => fill_vec!(output, idx, slice, f32),
-        TypeDescriptor::Float(FloatSize::U8) => fill_vec!(output, idx, slice, f64),
-        TypeDescriptor::Boolean => fill_vec!(output, idx, slice, bool),
-        TypeDescriptor::Enum(e) => fill(&e.base_type(), slice, output, idx),
+        TypeDescriptor::FixedAscii(n) if *n > len => TypeDescriptor::FixedAscii(len),
+        TypeDescriptor::FixedUnicode(n) if *n > len => TypeDescriptor::FixedUnicode(len),
         TypeDescriptor::Compound(c) => {
-            for (i, f) in c.fields.iter().enumerate() {
-                fill(&f.ty, &slice[f.offset..], output, idx + i);
-            }
+            let mut offset = 0;
+            let fields = c
+                .fields
+                .iter()
+                .map(|f| {
+                    let ty = shorten_strings(&f.ty, len);
+                    let field = CompoundField::new(&f.name, ty, offset, f.index);
+                    offset += field.ty.size();
+                    field
+                })
+                .collect();
+            TypeDescriptor::Compound(CompoundType {
+                fields,
+                size: offset,
+            })
         }
-        TypeDescriptor::FixedArray(ty, len) => {
-            let vec = output.array_vector(idx);
-            vec.set_child(&slice[..*len * ty.size()]);
+        TypeDescriptor::FixedArray(ty, n) => {
+            TypeDescriptor::FixedArray(Box::new(shorten_strings(ty, len)), *n)
         }
         TypeDescriptor::VarLenArray(ty) => {
-            let array = unsafe { slice.as_ptr().cast::<VarLenArray<u8>>().as_ref() }.unwrap();
-            let vec = output.list_vector(idx);
-            vec.set_child(&slice[..array.len() * ty.size()]);
+            TypeDescriptor::VarLenArray(Box::new(shorten_strings(ty, len)))
         }
-        TypeDescriptor::FixedAscii(len) | TypeDescriptor::FixedUnicode(len) => {
-            let vec = output.array_vector(idx);
-            vec.set_child(&slice[..*len]);
+        _ => dtype.clone(),
+    }
+}
+
+/// The first `len` bytes of a UTF-8 string at most, without splitting a
+/// character.
+fn truncate_str(bytes: &[u8], len: usize) -> &[u8] {
+    if bytes.len() <= len {
+        return bytes;
+    }
+    let mut end = len;
+    while end > 0 && bytes[end] & 0xc0 == 0x80 {
+        end -= 1;
+    }
+    &bytes[..end]
+}
+
+/// The descriptor with every integer and float widened to `float64`, letting
+/// HDF5 convert the items while reading.
+fn widen_to_double(dtype: &TypeDescriptor) -> TypeDescriptor {
+    match dtype {
+        TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) | TypeDescriptor::Float(_) => {
+            TypeDescriptor::Float(FloatSize::U8)
         }
-        TypeDescriptor::VarLenAscii => {
-            let array = unsafe { slice.as_ptr().cast::<VarLenAscii>().as_ref() }.unwrap();
-            let vec = output.list_vector(idx);
-            vec.set_child(array.as_bytes());
+        TypeDescriptor::Compound(c) => {
+            let mut offset = 0;
+            let fields = c
+                .fields
+                .iter()
+                .map(|f| {
+                    let ty = widen_to_double(&f.ty);
+                    let field = CompoundField::new(&f.name, ty, offset, f.index);
+                    offset += field.ty.size();
+                    field
+                })
+                .collect();
+            TypeDescriptor::Compound(CompoundType {
+                fields,
+                size: offset,
+            })
         }
-        TypeDescriptor::VarLenUnicode => {
-            let array = unsafe { slice.as_ptr().cast::<VarLenUnicode>().as_ref() }.unwrap();
-            let vec = output.list_vector(idx);
-            vec.set_child(array.as_bytes());
+        TypeDescriptor::FixedArray(ty, len) => {
+            TypeDescriptor::FixedArray(Box::new(widen_to_double(ty)), *len)
         }
-        TypeDescriptor::Reference(_) => {
-            let vec = output.flat_vector(idx);
-            vec.insert(0, &slice[..dtype.size()]);
+        TypeDescriptor::VarLenArray(ty) => {
+            TypeDescriptor::VarLenArray(Box::new(widen_to_double(ty)))
         }
+        _ => dtype.clone(),
     }
 }
 
-impl Hdf5ReadBindData {
-    fn new(path: &str, dataset: &str) -> hdf5::Result<Self> {
-        let file = hdf5::File::open(path)?;
-        let dataset = file.dataset(dataset)?;
-        let dtype = dataset.dtype()?.to_descriptor()?;
-        let data = dataset.read_raw_bytes(&dtype)?;
-        Ok(Self { dtype, data })
+/// Read a numeric item as `f64`.
+fn read_f64(dtype: &TypeDescriptor, slice: &[u8]) -> Option<f64> {
+    macro_rules! read {
+        ($t:ty) => {
+            unsafe { slice.as_ptr().cast::<$t>().read_unaligned() as f64 }
+        };
     }
+    Some(match dtype {
+        TypeDescriptor::Integer(IntSize::U1) => read!(i8),
+        TypeDescriptor::Integer(IntSize::U2) => read!(i16),
+        TypeDescriptor::Integer(IntSize::U4) => read!(i32),
+        TypeDescriptor::Integer(IntSize::U8) => read!(i64),
+        TypeDescriptor::Unsigned(IntSize::U1) => read!(u8),
+        TypeDescriptor::Unsigned(IntSize::U2) => read!(u16),
+        TypeDescriptor::Unsigned(IntSize::U4) => read!(u32),
+        TypeDescriptor::Unsigned(IntSize::U8) => read!(u64),
+        TypeDescriptor::Float(FloatSize::U4) => read!(f32),
+        TypeDescriptor::Float(FloatSize::U8) => read!(f64),
+        _ => return None,
+    })
+}
 
-    fn iter_dtype(&self) -> Vec<(Cow<'static, str>, LogicalTypeHandle)> {
-        iter_dtype(&self.dtype)
+struct Hdf5FileData {
+    /// The type of the items, with wide nested compounds flattened.
+    dtype: TypeDescriptor,
+    /// The items, empty if they are streamed, and unset until `pending` is
+    /// read.
+    data: OnceLock<Bytes>,
+    pending: Option<ItemRead>,
+    /// The number of items.
+    len: usize,
+    stream: Option<VlenStream>,
+    /// Set if the items are unpacked to `DOUBLE`.
+    scale: Option<Scale>,
+    /// The values of the requested attributes, `None` if optional and missing.
+    attrs: Vec<Option<AttributeValue>>,
+    /// The items read as NULL, with `nulls_from_fill`.
+    nulls: Vec<Vec<u8>>,
+    /// The buffers that `data` points into: the items of referenced regions,
+    /// or the samples of batches.
+    _items: Vec<Bytes>,
+}
+
+struct Hdf5ReadBindData {
+    dtype: TypeDescriptor,
+    /// The items of each matched file, in order.
+    files: Vec<Hdf5FileData>,
+    attrs: Vec<String>,
+    partitions: Vec<Partition>,
+    column_names: Option<Vec<String>>,
+    limits: Limits,
+    /// The style of each column of the dataset, if not as a number.
+    styles: Vec<Option<Style>>,
+    /// The new names of some columns of the dataset, from `schema_hint`.
+    renames: Vec<(String, String)>,
+    float_special: FloatSpecial,
+    /// Add the `__raw` column of the bytes of each item.
+    raw_compound: bool,
+    /// Add the `__index` column of the position of each row.
+    with_index: bool,
+    /// Emit the rows from the last to the first.
+    reverse: bool,
+    /// The time the scan may take, with `timeout_ms`.
+    timeout: Option<Duration>,
+}
+
+const RESULT_COLNAME: Cow<str> = Cow::Borrowed("result");
+
+fn logical_type(dtype: &TypeDescriptor) -> LogicalTypeHandle {
+    match dtype {
+        TypeDescriptor::Integer(size) => match size {
+            IntSize::U1 => LogicalTypeId::Tinyint,
+            IntSize::U2 => LogicalTypeId::Smallint,
+            IntSize::U4 => LogicalTypeId::Integer,
+            IntSize::U8 => LogicalTypeId::Bigint,
+        }
+        .into(),
+        TypeDescriptor::Unsigned(size) => match size {
+            IntSize::U1 => LogicalTypeId::UTinyint,
+            IntSize::U2 => LogicalTypeId::USmallint,
+            IntSize::U4 => LogicalTypeId::UInteger,
+            IntSize::U8 => LogicalTypeId::UBigint,
+        }
+        .into(),
+        TypeDescriptor::Float(size) => match size {
+            FloatSize::U4 => LogicalTypeId::Float,
+            FloatSize::U8 => LogicalTypeId::Double,
+        }
+        .into(),
+        TypeDescriptor::Boolean => LogicalTypeId::Boolean.into(),
+        TypeDescriptor::Enum(e) => logical_type(&e.base_type()),
+        TypeDescriptor::Compound(c) => {
+            let types = c
+                .fields
+                .iter()
+                .map(|f| (f.name.as_str(), logical_type(&f.ty)))
+                .collect::<Vec<_>>();
+            LogicalTypeHandle::struct_type(types.as_slice())
+        }
+        TypeDescriptor::FixedArray(ty, len) => {
+            LogicalTypeHandle::array(&logical_type(ty), *len as _)
+        }
+        TypeDescriptor::VarLenArray(ty) => LogicalTypeHandle::list(&logical_type(ty)),
+        TypeDescriptor::FixedAscii(_)
+        | TypeDescriptor::FixedUnicode(_)
+        | TypeDescriptor::VarLenAscii
+        | TypeDescriptor::VarLenUnicode => LogicalTypeId::Varchar.into(),
+        TypeDescriptor::Reference(_) => LogicalTypeId::Blob.into(),
     }
+}
 
-    fn project_dtype(&self, indices: &[duckdb::ffi::idx_t]) -> TypeDescriptor {
-        match &self.dtype {
-            TypeDescriptor::Compound(c) => {
-                let mut fields = vec![];
-                for i in indices {
-                    fields.push(c.fields[*i as usize].clone());
-                }
-                TypeDescriptor::Compound(CompoundType {
-                    fields,
-                    size: c.size,
+/// A readable name of an HDF5 datatype, e.g. `compound{a:int32, b:float64}`.
+fn dtype_name(dtype: &TypeDescriptor) -> String {
+    match dtype {
+        TypeDescriptor::Enum(e) => {
+            let members = e
+                .members
+                .iter()
+                .map(|m| {
+                    // Values are zero-extended, so signed ones are read as
+                    // their base to keep their sign.
+                    if e.signed {
+                        let value = read_i64(&e.base_type(), &m.value.to_ne_bytes()).unwrap();
+                        format!("{}={value}", m.name)
+                    } else {
+                        format!("{}={}", m.name, m.value)
+                    }
                 })
-            }
-            _ => self.dtype.clone(),
+                .collect::<Vec<_>>();
+            format!("enum{{{}}}", members.join(", "))
+        }
+        TypeDescriptor::Compound(c) => {
+            let fields = c
+                .fields
+                .iter()
+                .map(|f| format!("{}:{}", f.name, dtype_name(&f.ty)))
+                .collect::<Vec<_>>();
+            format!("compound{{{}}}", fields.join(", "))
         }
+        TypeDescriptor::FixedArray(ty, len) => format!("{}[{len}]", dtype_name(ty)),
+        TypeDescriptor::VarLenArray(ty) => format!("vlen<{}>", dtype_name(ty)),
+        TypeDescriptor::FixedAscii(len) => format!("ascii({len})"),
+        TypeDescriptor::FixedUnicode(len) => format!("utf8({len})"),
+        TypeDescriptor::VarLenAscii => "vlen<ascii>".to_string(),
+        TypeDescriptor::VarLenUnicode => "vlen<utf8>".to_string(),
+        TypeDescriptor::Reference(Reference::Object) => "reference(object)".to_string(),
+        TypeDescriptor::Reference(Reference::Region) => "reference(region)".to_string(),
+        _ => dtype.to_string(),
     }
+}
 
-    fn fill(&self, index: usize, dtype: &TypeDescriptor, output: &mut DataChunkHandle) {
-        let item_size = self.dtype.size();
-        if index * item_size >= self.data.len() {
-            output.set_len(0);
-        } else {
-            let data = &self.data[index * item_size..][..item_size];
-            fill(dtype, data, output, 0);
-            output.set_len(1);
+/// The name of a datatype class that has no descriptor.
+fn class_name(class: hdf5_sys::h5t::H5T_class_t) -> &'static str {
+    use hdf5_sys::h5t::H5T_class_t::*;
+    match class {
+        H5T_REFERENCE => "reference",
+        H5T_OPAQUE => "opaque",
+        H5T_BITFIELD => "bitfield",
+        H5T_COMPOUND => "compound",
+        H5T_TIME => "time",
+        _ => "unknown",
+    }
+}
+
+/// Append `fields` to `flat`, replacing the compounds with more than
+/// `max_fields` fields by their own fields, named `outer.inner`.
+fn flatten_fields(
+    fields: &[CompoundField],
+    prefix: &str,
+    base: usize,
+    max_fields: usize,
+    flat: &mut Vec<CompoundField>,
+) {
+    for f in fields {
+        let name = format!("{prefix}{}", f.name);
+        match &f.ty {
+            TypeDescriptor::Compound(c) if c.fields.len() > max_fields => flatten_fields(
+                &c.fields,
+                &format!("{name}."),
+                base + f.offset,
+                max_fields,
+                flat,
+            ),
+            ty => flat.push(CompoundField {
+                name,
+                ty: ty.clone(),
+                offset: base + f.offset,
+                index: flat.len(),
+            }),
         }
     }
 }
 
-struct Hdf5ReadInitData {
-    index: AtomicUsize,
-    dtype: TypeDescriptor,
+/// Flatten the wide nested compounds of a compound, which are unwieldy as
+/// `STRUCT`s. The offsets of the flattened fields still index the items.
+fn flatten_dtype(dtype: TypeDescriptor, max_fields: usize) -> TypeDescriptor {
+    match dtype {
+        TypeDescriptor::Compound(c) => {
+            let mut fields = vec![];
+            flatten_fields(&c.fields, "", 0, max_fields, &mut fields);
+            TypeDescriptor::Compound(CompoundType {
+                fields,
+                size: c.size,
+            })
+        }
+        _ => dtype,
+    }
 }
 
-impl Hdf5ReadInitData {
-    pub fn new(dtype: TypeDescriptor) -> Self {
-        Self {
-            index: AtomicUsize::new(0),
-            dtype,
+/// A readable name of a datatype, falling back to its class for types
+/// without a descriptor.
+fn datatype_name(dtype: &hdf5::Datatype) -> String {
+    match datatype::to_descriptor(dtype) {
+        Ok(dtype) => dtype_name(&dtype),
+        Err(_) => {
+            let class = hdf5::h5lock!(hdf5_sys::h5t::H5Tget_class(dtype.id()));
+            class_name(class).to_string()
         }
     }
 }
 
-struct Hdf5Read;
+/// The result columns of a dataset: one per field for compounds.
+fn iter_dtype(dtype: &TypeDescriptor) -> Vec<(Cow<'static, str>, LogicalTypeHandle)> {
+    match dtype {
+        TypeDescriptor::Compound(c) => c
+            .fields
+            .iter()
+            .map(|f| (Cow::Owned(f.name.clone()), logical_type(&f.ty)))
+            .collect(),
+        _ => vec![(RESULT_COLNAME, logical_type(dtype))],
+    }
+}
 
-impl VTab for Hdf5Read {
-    type InitData = Hdf5ReadInitData;
-    type BindData = Hdf5ReadBindData;
+macro_rules! fill_vec {
+    ($vector:expr, $row:expr, $slice:expr, $t:ty) => {{
+        let vec = FlatVector::from($vector);
+        unsafe {
+            vec.as_mut_ptr::<$t>()
+                .add($row)
+                .write($slice.as_ptr().cast::<$t>().read_unaligned())
+        };
+    }};
+}
 
-    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
-        let path = bind.get_parameter(0).to_string();
-        let dataset = bind.get_parameter(1).to_string();
-        let data = Hdf5ReadBindData::new(&path, &dataset)?;
-        for (name, dtype) in data.iter_dtype() {
-            bind.add_result_column(&name, dtype);
-        }
-        Ok(data)
+/// The elements of type `dtype` of the variable-length array in `slice`.
+fn vlen_elements<'a>(dtype: &TypeDescriptor, slice: &'a [u8]) -> &'a [u8] {
+    let array = unsafe { slice.as_ptr().cast::<VarLenArray<u8>>().as_ref() }.unwrap();
+    // An empty array may have a null pointer.
+    match array.len() {
+        0 => &[],
+        len => unsafe { std::slice::from_raw_parts(array.as_ptr(), len * dtype.size()) },
     }
+}
 
-    fn init(init: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
-        let bind_data = unsafe { init.get_bind_data::<Self::BindData>().as_ref() }.unwrap();
-        let dtype = bind_data.project_dtype(&init.get_column_indices());
-        Ok(Hdf5ReadInitData::new(dtype))
+/// Write one item of `dtype` into `vector` at `row`.
+fn fill(dtype: &TypeDescriptor, slice: &[u8], vector: ffi::duckdb_vector, row: usize) {
+    match dtype {
+        TypeDescriptor::Integer(IntSize::U1) => fill_vec!(vector, row, slice, i8),
+        TypeDescriptor::Integer(IntSize::U2) => fill_vec!(vector, row, slice, i16),
+        TypeDescriptor::Integer(IntSize::U4) => fill_vec!(vector, row, slice, i32),
+        TypeDescriptor::Integer(IntSize::U8) => fill_vec!(vector, row, slice, i64),
+        TypeDescriptor::Unsigned(IntSize::U1) => fill_vec!(vector, row, slice, u8),
+        TypeDescriptor::Unsigned(IntSize::U2) => fill_vec!(vector, row, slice, u16),
+        TypeDescriptor::Unsigned(IntSize::U4) => fill_vec!(vector, row, slice, u32),
+        TypeDescriptor::Unsigned(IntSize::U8) => fill_vec!(vector, row, slice, u64),
+        TypeDescriptor::Float(FloatSize::U4) => fill_vec!(vector, row, slice, f32),
+        TypeDescriptor::Float(FloatSize::U8) => fill_vec!(vector, row, slice, f64),
+        TypeDescriptor::Boolean => fill_vec!(vector, row, slice, bool),
+        TypeDescriptor::Enum(e) => fill(&e.base_type(), slice, vector, row),
+        TypeDescriptor::Compound(c) => {
+            for (i, f) in c.fields.iter().enumerate() {
+                let child = unsafe { ffi::duckdb_struct_vector_get_child(vector, i as _) };
+                fill(&f.ty, &slice[f.offset..], child, row);
+            }
+        }
+        TypeDescriptor::FixedArray(ty, len) => {
+            let child = unsafe { ffi::duckdb_array_vector_get_child(vector) };
+            for i in 0..*len {
+                fill(ty, &slice[i * ty.size()..], child, row * len + i);
+            }
+        }
+        TypeDescriptor::VarLenArray(ty) => {
+            let data = vlen_elements(ty, slice);
+            fill_list(ty, data, data.len() / ty.size(), vector, row);
+        }
+        TypeDescriptor::FixedAscii(len) | TypeDescriptor::FixedUnicode(len) => {
+            // Items are read as null-padded strings, so HDF5 has already
+            // stripped the padding of space-padded ones.
+            let data = &slice[..*len];
+            let mut end = data.iter().position(|&b| b == 0).unwrap_or(*len);
+            // A string truncated by HDF5 may end within a character.
+            if let Err(e) = std::str::from_utf8(&data[..end]) {
+                if e.error_len().is_none() {
+                    end = e.valid_up_to();
+                }
+            }
+            FlatVector::from(vector).insert(row, &data[..end]);
+        }
+        TypeDescriptor::VarLenAscii | TypeDescriptor::VarLenUnicode => {
+            // Both are a pointer to a null-terminated string, which is null
+            // for a null reference read as a path.
+            let string = unsafe { slice.as_ptr().cast::<VarLenAscii>().as_ref() }.unwrap();
+            let mut vec = FlatVector::from(vector);
+            match string.as_ptr().is_null() {
+                true => vec.set_null(row),
+                false => vec.insert(row, string.as_bytes()),
+            }
+        }
+        TypeDescriptor::Reference(_) => {
+            FlatVector::from(vector).insert(row, &slice[..dtype.size()]);
+        }
     }
+}
 
-    fn func(
-        func: &TableFunctionInfo<Self>,
-        output: &mut DataChunkHandle,
-    ) -> Result<(), Box<dyn Error>> {
-        let bind_data = func.get_bind_data();
-        let init_data = func.get_init_data();
-        let index = init_data.index.fetch_add(1, Ordering::Relaxed);
-        bind_data.fill(index, &init_data.dtype, output);
-        Ok(())
+/// Append `len` items of `dtype` to the child of a list vector, as the entry at `row`.
+fn fill_list(
+    dtype: &TypeDescriptor,
+    data: &[u8],
+    len: usize,
+    vector: ffi::duckdb_vector,
+    row: usize,
+) {
+    let list = ListVector::from(vector);
+    let offset = list.len();
+    unsafe { ffi::duckdb_list_vector_reserve(vector, (offset + len) as _) };
+    let child = unsafe { ffi::duckdb_list_vector_get_child(vector) };
+    for i in 0..len {
+        fill(dtype, &data[i * dtype.size()..], child, offset + i);
     }
+    list.set_len(offset + len);
+    set_list_entry(vector, row, offset, len);
+}
 
-    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
-        Some(vec![
-            LogicalTypeId::Varchar.into(),
-            LogicalTypeId::Varchar.into(),
-        ])
+/// Set the entry at `row` of a list vector. The entries of a nested list may
+/// be beyond a vector, which `ListVector::set_entry` rejects.
+fn set_list_entry(vector: ffi::duckdb_vector, row: usize, offset: usize, len: usize) {
+    let entries = unsafe { ffi::duckdb_vector_get_data(vector) }.cast::<ffi::duckdb_list_entry>();
+    unsafe {
+        entries.add(row).write(ffi::duckdb_list_entry {
+            offset: offset as _,
+            length: len as _,
+        })
+    };
+}
+
+/// Write the fields of a compound item as the entry at `row` of a `MAP`
+/// vector, from the names of the fields to their values.
+fn fill_map(c: &CompoundType, slice: &[u8], vector: ffi::duckdb_vector, row: usize) {
+    let list = ListVector::from(vector);
+    let (offset, len) = (list.len(), c.fields.len());
+    unsafe { ffi::duckdb_list_vector_reserve(vector, (offset + len) as _) };
+    let child = unsafe { ffi::duckdb_list_vector_get_child(vector) };
+    let keys = FlatVector::from(unsafe { ffi::duckdb_struct_vector_get_child(child, 0) });
+    let values = unsafe { ffi::duckdb_struct_vector_get_child(child, 1) };
+    for (i, f) in c.fields.iter().enumerate() {
+        keys.insert(offset + i, f.name.as_str());
+        fill(&f.ty, &slice[f.offset..], values, offset + i);
     }
+    list.set_len(offset + len);
+    set_list_entry(vector, row, offset, len);
+}
 
-    fn supports_pushdown() -> bool {
-        true
+/// Limits on the items of a column, for `fill_limited`.
+#[derive(Debug, Default, Clone, Copy)]
+struct Limits {
+    /// The number of items of each array or list to keep.
+    preview: Option<usize>,
+    /// The number of bytes of each variable-length string to keep.
+    max_string_len: Option<usize>,
+}
+
+/// Like `fill`, but keeping only the first items of an array or list and the
+/// first bytes of a variable-length string within `limits`.
+fn fill_limited(
+    dtype: &TypeDescriptor,
+    slice: &[u8],
+    vector: ffi::duckdb_vector,
+    row: usize,
+    limits: Limits,
+) {
+    match (dtype, limits.preview, limits.max_string_len) {
+        (TypeDescriptor::FixedArray(ty, len), Some(k), _) if *len > k => {
+            let child = unsafe { ffi::duckdb_array_vector_get_child(vector) };
+            for i in 0..k {
+                fill(ty, &slice[i * ty.size()..], child, row * k + i);
+            }
+        }
+        (TypeDescriptor::VarLenArray(ty), Some(k), _) => {
+            let data = vlen_elements(ty, slice);
+            fill_list(ty, data, (data.len() / ty.size()).min(k), vector, row);
+        }
+        (TypeDescriptor::VarLenAscii | TypeDescriptor::VarLenUnicode, _, Some(len)) => {
+            // Both are a pointer to a null-terminated string.
+            let string = unsafe { slice.as_ptr().cast::<VarLenAscii>().as_ref() }.unwrap();
+            match string.as_ptr().is_null() {
+                true => fill(dtype, slice, vector, row),
+                false => FlatVector::from(vector).insert(row, truncate_str(string.as_bytes(), len)),
+            }
+        }
+        _ => fill(dtype, slice, vector, row),
     }
 }
 
-#[duckdb_entrypoint_c_api()]
+/// How an integer column is written instead of as a number, or a compound
+/// column instead of as a `STRUCT`.
+#[derive(Debug, Clone)]
+enum Style {
+    /// A `BOOLEAN` array of its bits, least significant first.
+    Bits,
+    /// A `TIMESTAMP`, counting this many units per second since the epoch.
+    Time(i64),
+    /// A `VARCHAR` label for each value, `NULL` for values without one.
+    Labels(Vec<(i64, String)>),
+    /// A `MAP` of the names of the fields of a compound to their values,
+    /// which share a type.
+    Map,
+    /// A `DOUBLE` for each unsigned 64-bit integer.
+    Double,
+    /// A `VARCHAR` of the decimal digits of each unsigned 64-bit integer.
+    Digits,
+}
+
+impl Style {
+    fn logical_type(&self, dtype: &TypeDescriptor) -> LogicalTypeHandle {
+        match self {
+            Self::Bits => {
+                LogicalTypeHandle::array(&LogicalTypeId::Boolean.into(), (dtype.size() * 8) as _)
+            }
+            Self::Time(_) => LogicalTypeId::Timestamp.into(),
+            // Fixed arrays of enums are arrays of labels.
+            Self::Labels(_) => match dtype {
+                TypeDescriptor::FixedArray(_, len) => {
+                    LogicalTypeHandle::array(&LogicalTypeId::Varchar.into(), *len as _)
+                }
+                _ => LogicalTypeId::Varchar.into(),
+            },
+            Self::Double | Self::Digits => {
+                let ty: LogicalTypeHandle = match self {
+                    Self::Double => LogicalTypeId::Double.into(),
+                    _ => LogicalTypeId::Varchar.into(),
+                };
+                match dtype {
+                    TypeDescriptor::FixedArray(_, len) => LogicalTypeHandle::array(&ty, *len as _),
+                    _ => ty,
+                }
+            }
+            Self::Map => match dtype {
+                TypeDescriptor::FixedArray(item, len) => {
+                    LogicalTypeHandle::array(&self.logical_type(item), *len as _)
+                }
+                TypeDescriptor::Compound(c) => LogicalTypeHandle::map(
+                    &LogicalTypeId::Varchar.into(),
+                    &logical_type(&c.fields[0].ty),
+                ),
+                dtype => logical_type(dtype),
+            },
+        }
+    }
+}
+
+/// The value of an integer of `dtype`, with unsigned 64-bit integers
+/// wrapping around.
+fn read_i64(dtype: &TypeDescriptor, slice: &[u8]) -> Option<i64> {
+    macro_rules! read {
+        ($t:ty) => {
+            unsafe { slice.as_ptr().cast::<$t>().read_unaligned() as i64 }
+        };
+    }
+    Some(match dtype {
+        TypeDescriptor::Integer(IntSize::U1) => read!(i8),
+        TypeDescriptor::Integer(IntSize::U2) => read!(i16),
+        TypeDescriptor::Integer(IntSize::U4) => read!(i32),
+        TypeDescriptor::Integer(IntSize::U8) => read!(i64),
+        TypeDescriptor::Unsigned(IntSize::U1) => read!(u8),
+        TypeDescriptor::Unsigned(IntSize::U2) => read!(u16),
+        TypeDescriptor::Unsigned(IntSize::U4) => read!(u32),
+        TypeDescriptor::Unsigned(IntSize::U8) => read!(u64),
+        TypeDescriptor::Enum(e) => return read_i64(&e.base_type(), slice),
+        _ => return None,
+    })
+}
+
+/// Write an integer of `dtype` into `vector` at `row`, in `style`.
+fn fill_styled(
+    style: &Style,
+    dtype: &TypeDescriptor,
+    slice: &[u8],
+    vector: ffi::duckdb_vector,
+    row: usize,
+) {
+    if let TypeDescriptor::FixedArray(item, len) = dtype {
+        let child = unsafe { ffi::duckdb_array_vector_get_child(vector) };
+        for i in 0..*len {
+            fill_styled(style, item, &slice[i * item.size()..], child, row * len + i);
+        }
+        return;
+    }
+    // Only maps are compounds.
+    if let TypeDescriptor::Compound(c) = dtype {
+        fill_map(c, slice, vector, row);
+        return;
+    }
+    let Some(value) = read_i64(dtype, slice) else {
+        return;
+    };
+    match style {
+        Style::Bits => {
+            let width = dtype.size() * 8;
+            let child = FlatVector::from(unsafe { ffi::duckdb_array_vector_get_child(vector) });
+            let bits = unsafe {
+                std::slice::from_raw_parts_mut(child.as_mut_ptr::<bool>(), (row + 1) * width)
+            };
+            for (i, bit) in bits[row * width..].iter_mut().enumerate() {
+                *bit = value as u64 >> i & 1 != 0;
+            }
+        }
+        Style::Time(per_second) => {
+            // Timestamps are in microseconds, and times beyond them are NULL.
+            let micros = value as i128 * 1_000_000 / *per_second as i128;
+            let mut vec = FlatVector::from(vector);
+            match i64::try_from(micros) {
+                Ok(micros) => unsafe { vec.as_mut_ptr::<i64>().add(row).write(micros) },
+                Err(_) => vec.set_null(row),
+            }
+        }
+        Style::Labels(labels) => {
+            let mut vec = FlatVector::from(vector);
+            match labels.iter().find(|(v, _)| *v == value) {
+                Some((_, label)) => vec.insert(row, label.as_str()),
+                None => vec.set_null(row),
+            }
+        }
+        Style::Map => {}
+        Style::Double => {
+            let vec = FlatVector::from(vector);
+            unsafe { vec.as_mut_ptr::<f64>().add(row).write(value as u64 as f64) };
+        }
+        Style::Digits => {
+            FlatVector::from(vector).insert(row, (value as u64).to_string().as_str());
+        }
+    }
+}
+
+/// Write `count` items of `dtype`, `stride` bytes apart in `data`, into
+/// `vector` from row 0. Numbers are copied a vector at a time, in the flat
+/// layout that DuckDB also exports to Arrow, unless they have a `style`.
+fn fill_column(
+    dtype: &TypeDescriptor,
+    data: &[u8],
+    stride: usize,
+    count: usize,
+    vector: ffi::duckdb_vector,
+    limits: Limits,
+    style: Option<&Style>,
+) {
+    match (dtype, style) {
+        (_, Some(style)) => {
+            for i in 0..count {
+                fill_styled(style, dtype, &data[i * stride..], vector, i);
+            }
+        }
+        (
+            TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) | TypeDescriptor::Float(_),
+            None,
+        ) => {
+            let size = dtype.size();
+            let dst = FlatVector::from(vector).as_mut_ptr::<u8>();
+            if stride == size {
+                let data = &data[..count * size];
+                unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), dst, data.len()) };
+            } else {
+                for i in 0..count {
+                    let item = &data[i * stride..][..size];
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(item.as_ptr(), dst.add(i * size), size)
+                    };
+                }
+            }
+        }
+        _ => {
+            for i in 0..count {
+                fill_limited(dtype, &data[i * stride..], vector, i, limits);
+            }
+        }
+    }
+}
+
+/// Write one item of `dtype` as a row of `output`, spreading compound fields
+/// over the `columns` in their `styles`, within `limits`.
+fn fill_row(
+    dtype: &TypeDescriptor,
+    slice: &[u8],
+    output: &mut DataChunkHandle,
+    columns: &[usize],
+    styles: &[Option<Style>],
+    row: usize,
+    limits: Limits,
+) {
+    let fill_column = |dtype: &TypeDescriptor, slice: &[u8], col: usize, style: &Option<Style>| {
+        let vector = unsafe { ffi::duckdb_data_chunk_get_vector(output.get_ptr(), col as _) };
+        match style {
+            Some(style) => fill_styled(style, dtype, slice, vector, row),
+            None => fill_limited(dtype, slice, vector, row, limits),
+        }
+    };
+    match dtype {
+        TypeDescriptor::Compound(c) => {
+            for ((f, col), style) in c.fields.iter().zip(columns).zip(styles) {
+                fill_column(&f.ty, &slice[f.offset..], *col, style);
+            }
+        }
+        _ => {
+            if let (Some(col), Some(style)) = (columns.first(), styles.first()) {
+                fill_column(dtype, slice, *col, style)
+            }
+        }
+    }
+}
+
+/// Unique column names: DuckDB compares them case-insensitively, so later
+/// duplicates get a `_1`, `_2`... suffix. Flattened fields and attributes
+/// may take the name of another column.
+fn unique_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut columns: Vec<String> = vec![];
+    for name in names {
+        let taken = |c: &str| columns.iter().any(|t| t.eq_ignore_ascii_case(c));
+        let mut column = name.to_string();
+        let mut suffix = 0;
+        while taken(&column) {
+            suffix += 1;
+            column = format!("{name}_{suffix}");
+        }
+        columns.push(column);
+    }
+    columns
+}
+
+/// Open an HDF5 file image held in memory, with the core driver.
+fn open_image(image: &[u8]) -> hdf5::Result<hdf5::File> {
+    // Open images are told apart by name.
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = format!("blob-{}", COUNTER.fetch_add(1, Ordering::Relaxed));
+    let name = std::ffi::CString::new(name).unwrap();
+    let fapl = hdf5::plist::FileAccess::build()
+        .core_options(64 * 1024, false)
+        .finish()?;
+    hdf5::h5call!(hdf5_sys::h5p::H5Pset_file_image(
+        fapl.id(),
+        image.as_ptr() as *mut _,
+        image.len()
+    ))?;
+    let id = hdf5::h5call!(hdf5_sys::h5f::H5Fopen(
+        name.as_ptr(),
+        hdf5_sys::h5f::H5F_ACC_RDONLY,
+        fapl.id()
+    ))?;
+    unsafe { hdf5::from_id(id) }
+}
+
+/// The union of the compound fields of every file, in order of appearance.
+/// Fields with the same name must have the same type.
+fn union_dtype(files: &[Hdf5FileData], paths: &[String]) -> Result<TypeDescriptor, Box<dyn Error>> {
+    let mismatch = |path: &str| format!("datatype of '{path}' does not match '{}'", paths[0]);
+    let TypeDescriptor::Compound(_) = &files[0].dtype else {
+        return match files
+            .iter()
+            .zip(paths)
+            .find(|(f, _)| f.dtype != files[0].dtype)
+        {
+            Some((_, path)) => Err(mismatch(path).into()),
+            None => Ok(files[0].dtype.clone()),
+        };
+    };
+    let mut fields: Vec<CompoundField> = vec![];
+    for (file, path) in files.iter().zip(paths) {
+        let TypeDescriptor::Compound(c) = &file.dtype else {
+            return Err(mismatch(path).into());
+        };
+        for field in &c.fields {
+            match fields.iter().find(|f| f.name == field.name) {
+                Some(f) if f.ty != field.ty => {
+                    return Err(format!(
+                        "datatype of field '{}' of '{path}' does not match '{}'",
+                        field.name, paths[0]
+                    )
+                    .into())
+                }
+                Some(_) => {}
+                None => fields.push(field.clone()),
+            }
+        }
+    }
+    // Only the names and types of the union are used; the items are read
+    // with the fields of each file.
+    let mut size = 0;
+    let fields = fields
+        .into_iter()
+        .enumerate()
+        .map(|(index, f)| {
+            let field = CompoundField::new(&f.name, f.ty.clone(), size, index);
+            size += f.ty.size();
+            field
+        })
+        .collect();
+    Ok(TypeDescriptor::Compound(CompoundType { fields, size }))
+}
+
+/// The latest version of the superblock, which every HDF5 release the
+/// extension links against, 1.12 or later, supports.
+const LATEST_SUPERBLOCK: u8 = 3;
+
+/// The version of the superblock of the file at `path`, after a user block
+/// of 0, 512, 1024... bytes.
+fn superblock_version(path: &str) -> Option<u8> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut offset = 0;
+    while offset + 9 <= len {
+        let mut header = [0u8; 9];
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        file.read_exact(&mut header).ok()?;
+        if header[..8] == *b"\x89HDF\r\n\x1a\n" {
+            return Some(header[8]);
+        }
+        offset = (offset * 2).max(512);
+    }
+    None
+}
+
+/// Open a file from disk, explaining a failure with `superblock_error`.
+fn open_path(path: &str) -> Result<hdf5::File, Hdf5ReadError> {
+    hdf5::File::open(path).map_err(|err| open_error(path, err))
+}
+
+/// The error of HDF5 failing to open the file at `path`: that it doesn't
+/// exist, or else why HDF5 can't open it.
+fn open_error(path: &str, err: hdf5::Error) -> Hdf5ReadError {
+    match Path::new(path).exists() {
+        true => Hdf5ReadError::FileOpen {
+            path: path.to_string(),
+            source: superblock_error(path, err),
+        },
+        false => Hdf5ReadError::FileNotFound {
+            path: path.to_string(),
+        },
+    }
+}
+
+/// The error of HDF5 failing to open the file at `path`, or else, if its
+/// superblock is newer than the linked library supports, one saying so.
+fn superblock_error(path: &str, err: hdf5::Error) -> hdf5::Error {
+    let (major, minor, release) = hdf5::library_version();
+    match superblock_version(path) {
+        Some(version) if version > LATEST_SUPERBLOCK => format!(
+            "'{path}' requires a newer HDF5, as its superblock version is {version}; \
+             linked library is {major}.{minor}.{release}"
+        )
+        .into(),
+        _ => err,
+    }
+}
+
+/// Open a file from disk, or with `image`, read it whole and open it from
+/// memory, replacing the many small reads of HDF5 with a single one.
+fn open_file(path: &str, options: &Hdf5ReadOptions) -> Result<hdf5::File, Box<dyn Error>> {
+    if options.swmr {
+        return open_swmr(path);
+    }
+    match options.driver {
+        Driver::Sec2 if options.alignment.is_none() => {}
+        // Neither is combined with `image`.
+        Driver::Sec2 | Driver::Direct => {
+            return Ok(open_direct(path, options.driver, options.alignment)
+                .map_err(|err| open_error(path, err))?);
+        }
+        Driver::Family => return open_family(path, options.member_size),
+        Driver::Split => return open_split(path),
+    }
+    if let Some(offset) = options.user_block_offset {
+        return open_embedded(path, offset);
+    }
+    if !options.image {
+        return Ok(open_path(path)?);
+    }
+    let size = std::fs::metadata(path)
+        .map_err(|err| Hdf5ReadError::io(path, err))?
+        .len();
+    if size > MAX_IMAGE_SIZE {
+        return Err(Hdf5ReadError::TooLarge(format!(
+            "'{path}' is too large to read as an image, as it has {size} bytes, more than {MAX_IMAGE_SIZE}"
+        ))
+        .into());
+    }
+    let image = std::fs::read(path).map_err(|err| Hdf5ReadError::io(path, err))?;
+    Ok(open_image(&image).map_err(|err| open_error(path, err))?)
+}
+
+/// Open the HDF5 file that starts `offset` bytes into the file at `path`,
+/// after a user block or appended to other data, as an image of the rest.
+/// HDF5 itself only finds a superblock at 0 or a power of 2 from 512.
+fn open_embedded(path: &str, offset: u64) -> Result<hdf5::File, Box<dyn Error>> {
+    let io = |err| Hdf5ReadError::io(path, err);
+    let mut file = std::fs::File::open(path).map_err(io)?;
+    let size = file.metadata().map_err(io)?.len();
+    if offset >= size {
+        return Err(format!(
+            "user_block_offset {offset} is past the end of '{path}', which has {size} bytes"
+        )
+        .into());
+    }
+    if size - offset > MAX_IMAGE_SIZE {
+        return Err(Hdf5ReadError::TooLarge(format!(
+            "'{path}' is too large to read as an image, as it has {} bytes after \
+             user_block_offset, more than {MAX_IMAGE_SIZE}",
+            size - offset
+        ))
+        .into());
+    }
+    file.seek(SeekFrom::Start(offset)).map_err(io)?;
+    let mut image = vec![];
+    file.read_to_end(&mut image).map_err(io)?;
+    Ok(open_image(&image)
+        .map_err(|err| format!("no HDF5 file at user_block_offset {offset} of '{path}': {err}"))?)
+}
+
+/// Open a file for SWMR reads, which its superblock must support.
+fn open_swmr(path: &str) -> Result<hdf5::File, Box<dyn Error>> {
+    use hdf5_sys::h5f;
+    let name = std::ffi::CString::new(path)?;
+    let id = hdf5::h5call!(h5f::H5Fopen(
+        name.as_ptr(),
+        h5f::H5F_ACC_RDONLY | h5f::H5F_ACC_SWMR_READ,
+        hdf5_sys::h5p::H5P_DEFAULT
+    ))
+    .map_err(|err| open_error(path, err))?;
+    let file: hdf5::File = unsafe { hdf5::from_id(id) }?;
+    let mut info = h5f::H5F_info2_t::default();
+    hdf5::h5call!(h5f::H5Fget_info2(file.id(), &mut info))?;
+    // HDF5 opens older files too, but cannot read them while being written.
+    if info.super_.version < 3 {
+        return Err(format!(
+            "'{path}' does not support SWMR, as its superblock version is {}, not 3 or later",
+            info.super_.version
+        )
+        .into());
+    }
+    Ok(file)
+}
+
+/// Whether a parameter is NULL, which `Value` can't render: DuckDB throws
+/// when converting a NULL value, and `Value` has no other accessor.
+fn is_null(value: &Value) -> bool {
+    // Only the `Debug` output of `Value` shows the `duckdb_value` it wraps,
+    // e.g. `Value { ptr: 0x5f3a2c0 }`; without one, it counts as not NULL.
+    let debug = format!("{value:?}");
+    let ptr = debug.split_once("0x").and_then(|(_, hex)| {
+        let end = hex
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(hex.len());
+        usize::from_str_radix(&hex[..end], 16).ok()
+    });
+    match ptr {
+        Some(ptr) if ptr != 0 => unsafe { ffi::duckdb_is_null_value(ptr as ffi::duckdb_value) },
+        _ => false,
+    }
+}
+
+/// Open the dataset at `path` of `file`, named `name` in errors, always
+/// from the root group, with or without a leading `/`.
+fn open_dataset(file: &hdf5::File, path: &str, name: &str) -> Result<hdf5::Dataset, Hdf5ReadError> {
+    let path = match path.strip_prefix('/') {
+        Some(_) => Cow::Borrowed(path),
+        None => Cow::Owned(format!("/{path}")),
+    };
+    file.dataset(&path)
+        .map_err(|err| match file.link_exists(&path) {
+            true => err.into(),
+            false => Hdf5ReadError::MissingDataset {
+                path: name.to_string(),
+                dataset: path.into_owned(),
+            },
+        })
+}
+
+/// A key of the object at `location`, the same through every hard or soft
+/// link to it, to visit each object of a file once.
+fn object_key(location: &hdf5::Location) -> hdf5::Result<String> {
+    use hdf5_sys::h5o;
+    let mut info = std::mem::MaybeUninit::<h5o::H5O_info2_t>::uninit();
+    hdf5::h5call!(h5o::H5Oget_info3(
+        location.id(),
+        info.as_mut_ptr(),
+        h5o::H5O_INFO_BASIC
+    ))?;
+    let info = unsafe { info.assume_init() };
+    let mut token = std::ptr::null_mut();
+    hdf5::h5call!(h5o::H5Otoken_to_str(location.id(), &info.token, &mut token))?;
+    let key = format!("{}:{}", info.fileno, unsafe {
+        std::ffi::CStr::from_ptr(token).to_string_lossy()
+    });
+    unsafe { hdf5_sys::h5::H5free_memory(token.cast()) };
+    Ok(key)
+}
+
+/// The only dataset of a file, searched recursively.
+fn single_dataset(file: &hdf5::File, path: &str) -> Result<hdf5::Dataset, Box<dyn Error>> {
+    let mut datasets = vec![];
+    let mut groups = vec![file.as_group()?];
+    // Objects linked more than once, or from within themselves, count once.
+    let mut visited = HashSet::from([object_key(file)?]);
+    while let Some(group) = groups.pop() {
+        for dataset in group.datasets()? {
+            if visited.insert(object_key(&dataset)?) {
+                datasets.push(dataset);
+            }
+        }
+        for child in group.groups()? {
+            if visited.insert(object_key(&child)?) {
+                groups.push(child);
+            }
+        }
+    }
+    if datasets.len() == 1 {
+        Ok(datasets.pop().unwrap())
+    } else {
+        Err(format!(
+            "dataset name must not be NULL, as '{path}' contains {} datasets",
+            datasets.len()
+        )
+        .into())
+    }
+}
+
+/// Open `dataset` of `file` again, with a chunk cache of `chunk_cache`
+/// bytes, and looking for its external files next to the file with `origin`.
+fn reopen(
+    file: &hdf5::File,
+    dataset: hdf5::Dataset,
+    chunk_cache: Option<usize>,
+    origin: bool,
+) -> hdf5::Result<hdf5::Dataset> {
+    let dapl: hdf5::plist::PropertyList = unsafe {
+        hdf5::from_id(hdf5::h5call!(hdf5_sys::h5p::H5Pcreate(
+            *hdf5::globals::H5P_DATASET_ACCESS
+        ))?)?
+    };
+    if let Some(bytes) = chunk_cache {
+        hdf5::h5call!(hdf5_sys::h5p::H5Pset_chunk_cache(
+            dapl.id(),
+            hdf5_sys::h5d::H5D_CHUNK_CACHE_NSLOTS_DEFAULT,
+            bytes,
+            hdf5_sys::h5d::H5D_CHUNK_CACHE_W0_DEFAULT as _
+        ))?;
+    }
+    if origin {
+        hdf5::h5call!(hdf5_sys::h5p::H5Pset_efile_prefix(
+            dapl.id(),
+            c"${ORIGIN}".as_ptr()
+        ))?;
+    }
+    let name = std::ffi::CString::new(dataset.name()).unwrap();
+    // The external file prefix can't change while the dataset is open.
+    drop(dataset);
+    let id = hdf5::h5call!(hdf5_sys::h5d::H5Dopen2(file.id(), name.as_ptr(), dapl.id()))?;
+    unsafe { hdf5::from_id(id) }
+}
+
+/// The named fields of a compound dataset, packed in the given order. HDF5
+/// converts compounds by field name, so other fields are never read, even if
+/// their type has no descriptor.
+fn select_fields(
+    dataset: &hdf5::Dataset,
+    file_dtype: &hdf5::Datatype,
+    columns: &[String],
+    name: &str,
+) -> Result<TypeDescriptor, Box<dyn Error>> {
+    use hdf5_sys::h5t;
+    let class = hdf5::h5lock!(h5t::H5Tget_class(file_dtype.id()));
+    if class != h5t::H5T_class_t::H5T_COMPOUND {
+        return Err(format!(
+            "columns requires a compound dataset, but '{}' of '{name}' is {}",
+            dataset.name(),
+            datatype_name(file_dtype)
+        )
+        .into());
+    }
+    let mut fields = vec![];
+    let mut offset = 0;
+    for (index, column) in columns.iter().enumerate() {
+        let field_name = std::ffi::CString::new(column.as_str())?;
+        let member = hdf5::h5lock!(h5t::H5Tget_member_index(
+            file_dtype.id(),
+            field_name.as_ptr()
+        ));
+        if member < 0 {
+            return Err(format!(
+                "field '{column}' not found in '{}' of '{name}'",
+                dataset.name()
+            )
+            .into());
+        }
+        let member_dtype: hdf5::Datatype = unsafe {
+            hdf5::from_id(hdf5::h5call!(h5t::H5Tget_member_type(
+                file_dtype.id(),
+                member as _
+            ))?)?
+        };
+        let ty = datatype::to_field_descriptor(&member_dtype).map_err(|_| {
+            Hdf5ReadError::UnsupportedType {
+                dataset: dataset.name(),
+                field: Some(column.to_string()),
+                dtype: datatype_name(&member_dtype),
+            }
+        })?;
+        let size = ty.size();
+        fields.push(CompoundField::new(column, ty, offset, index));
+        offset += size;
+    }
+    Ok(TypeDescriptor::Compound(CompoundType {
+        fields,
+        size: offset,
+    }))
+}
+
+/// The source files and datasets of a virtual dataset. Only the names are
+/// read, as `virtual_map` fails on some selections.
+fn virtual_sources(dataset: &hdf5::Dataset) -> hdf5::Result<Vec<(String, String)>> {
+    use hdf5_sys::h5p;
+    let dcpl = dataset.dcpl()?;
+    let mut len = 0;
+    hdf5::h5call!(h5p::H5Pget_virtual_count(dcpl.id(), &mut len))?;
+    let name = |i, get: unsafe extern "C" fn(_, _, _, _) -> _| -> hdf5::Result<String> {
+        let size = hdf5::h5call!(get(dcpl.id(), i, std::ptr::null_mut(), 0))?;
+        let mut buffer = vec![0u8; size as usize + 1];
+        hdf5::h5call!(get(dcpl.id(), i, buffer.as_mut_ptr().cast(), buffer.len()))?;
+        buffer.truncate(size as usize);
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    };
+    (0..len)
+        .map(|i| {
+            Ok((
+                name(i, h5p::H5Pget_virtual_filename)?,
+                name(i, h5p::H5Pget_virtual_dsetname)?,
+            ))
+        })
+        .collect()
+}
+
+/// The first row and the number of rows of the vector `chunk` of `total`
+/// rows, in vectors of `capacity` rows: every vector but the last is whole,
+/// and vectors past the end are empty.
+fn vector_rows(chunk: usize, capacity: usize, total: usize) -> (usize, usize) {
+    let start = (chunk * capacity).min(total);
+    (start, (total - start).min(capacity))
+}
+
+/// Fail on the missing external files of a dataset of a file named `name`,
+/// and tell whether they are next to the file. HDF5 looks for relative
+/// external files in the working directory, or under HDF5_EXTFILE_PREFIX.
+fn check_external_files(dataset: &hdf5::Dataset, name: &str) -> Result<bool, Box<dyn Error>> {
+    let files = dataset.dcpl()?.get_external()?;
+    if files.is_empty() || std::env::var_os("HDF5_EXTFILE_PREFIX").is_some() {
+        return Ok(false);
+    }
+    let dir = Path::new(name).parent().unwrap_or(Path::new(""));
+    if files.iter().all(|f| dir.join(&f.name).exists()) {
+        return Ok(true);
+    }
+    if let Some(file) = files.iter().find(|f| !Path::new(&f.name).exists()) {
+        return Err(format!(
+            "external file '{}' of '{}' of '{name}' is missing",
+            file.name,
+            dataset.name()
+        )
+        .into());
+    }
+    Ok(false)
+}
+
+/// Fail on a dataset compressed with szip in a build of HDF5 without it,
+/// which HDF5 would only report when reading a chunk.
+fn check_szip(dataset: &hdf5::Dataset, name: &str) -> Result<(), Box<dyn Error>> {
+    let szip = |f: &Filter| matches!(f, Filter::SZip(..));
+    if dataset.filters().iter().any(szip) && !hdf5::filters::szip_available() {
+        return Err(format!(
+            "szip decompression not available in this build, needed by '{}' of '{name}'",
+            dataset.name()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Fail on the missing source files of a virtual dataset of a file named
+/// `name`, which HDF5 would silently read as fill values.
+fn check_virtual_sources(dataset: &hdf5::Dataset, name: &str) -> Result<(), Box<dyn Error>> {
+    if dataset.layout() != Layout::Virtual {
+        return Ok(());
+    }
+    // HDF5 also looks for relative sources under HDF5_VDS_PREFIX and next to
+    // the virtual dataset.
+    let prefix = std::env::var_os("HDF5_VDS_PREFIX").map(PathBuf::from);
+    let dir = Path::new(name).parent().map(Path::to_path_buf);
+    for (file, _) in virtual_sources(dataset)? {
+        // "." is the file of the virtual dataset itself.
+        if file == "." {
+            continue;
+        }
+        let source = Path::new(&file);
+        let found = source.exists()
+            || (source.is_relative()
+                && [&prefix, &dir]
+                    .into_iter()
+                    .flatten()
+                    .any(|d| d.join(source).exists()));
+        if !found {
+            return Err(format!(
+                "source '{file}' of virtual dataset '{}' of '{name}' is missing",
+                dataset.name()
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// The values of the `_FillValue` and `missing_value` attributes of
+/// `dataset`, of a file named `name`, as items of `dtype`. Both may list many
+/// values, as the CF conventions allow for `missing_value`.
+fn missing_values(
+    dataset: &hdf5::Dataset,
+    attr_names: &[String],
+    dtype: &TypeDescriptor,
+    name: &str,
+) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+    if !matches!(
+        dtype,
+        TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) | TypeDescriptor::Float(_)
+    ) {
+        return Err(format!(
+            "nulls_from_fill requires numeric datasets, but '{}' of '{name}' is {}",
+            dataset.name(),
+            dtype_name(dtype)
+        )
+        .into());
+    }
+    let mut values = vec![];
+    for attr in ["_FillValue", "missing_value"] {
+        if !attr_names.iter().any(|a| a == attr) {
+            continue;
+        }
+        let data = dataset.attr(attr)?.read_raw_bytes(dtype).map_err(|_| {
+            format!(
+                "attribute '{attr}' of '{}' of '{name}' is not a number",
+                dataset.name()
+            )
+        })?;
+        values.extend(data.chunks_exact(dtype.size()).map(<[u8]>::to_vec));
+    }
+    Ok(values)
+}
+
+impl Hdf5FileData {
+    /// Read a dataset of `file`, named `name` in errors. With `stream`, its
+    /// items are only read during the scan: in batches for a dataset of
+    /// variable-length items whose selection allows it, or else whole when
+    /// first scanned, unless masked.
+    fn read(
+        file: &hdf5::File,
+        name: &str,
+        dataset: Option<&str>,
+        options: &Hdf5ReadOptions,
+        stream: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        let dataset = match dataset {
+            Some(dataset) => open_dataset(file, dataset, name)?,
+            None => single_dataset(file, name)?,
+        };
+        let dataset = match options.chunk_cache_bytes {
+            Some(bytes) if dataset.is_chunked() => reopen(file, dataset, Some(bytes), false)?,
+            _ => dataset,
+        };
+        check_virtual_sources(&dataset, name)?;
+        check_szip(&dataset, name)?;
+        let attr_names = dataset.attr_names()?;
+        let mut attrs = vec![];
+        for attr in &options.attrs {
+            if attr_names.contains(attr) {
+                attrs.push(Some(AttributeValue::read(&dataset.attr(attr)?)?));
+            } else if options.attrs_optional {
+                attrs.push(None);
+            } else {
+                return Err(format!(
+                    "attribute '{attr}' not found on '{}' of '{name}'",
+                    dataset.name()
+                )
+                .into());
+            }
+        }
+        let scale = options
+            .apply_scale
+            .then(|| Scale::read(&dataset))
+            .transpose()?;
+        // Attributes can't be iterated with another external file prefix, so
+        // they are read before.
+        let dataset = if check_external_files(&dataset, name)? {
+            reopen(file, dataset, None, true)?
+        } else {
+            dataset
+        };
+        let file_dtype = dataset.dtype()?;
+        if options.deref_regions && is_region_reference(&file_dtype) {
+            let regions = Regions::read(&dataset, name, options.all_double)?;
+            return Ok(Self {
+                len: dataset_len(&dataset)?,
+                stream: None,
+                dtype: regions.dtype,
+                data: Bytes::from(regions.data).into(),
+                pending: None,
+                scale: None,
+                attrs,
+                nulls: vec![],
+                _items: regions.items.into_iter().map(Bytes::from).collect(),
+            });
+        }
+        if options.deref && is_object_reference(&file_dtype) {
+            let targets = Targets::read(&dataset, name, options.all_double)?;
+            return Ok(Self {
+                len: dataset_len(&dataset)?,
+                stream: None,
+                dtype: flatten_dtype(targets.dtype, options.max_struct_fields),
+                data: Bytes::from(targets.data).into(),
+                pending: None,
+                scale: None,
+                attrs,
+                nulls: vec![],
+                _items: vec![],
+            });
+        }
+        let selection = if options.hyperslab.is_empty() {
+            None
+        } else {
+            Some(match options.squeeze {
+                true => options.hyperslab.resolve_squeezed(&dataset.shape())?,
+                false => options.hyperslab.resolve(&dataset.shape())?,
+            })
+        };
+        if (options.batch.is_some()
+            || options.mask_dataset.is_some()
+            || (options.order == Order::Fortran && dataset.ndim() > 1))
+            && reference_array_type(&file_dtype)?.is_some()
+        {
+            return Err(format!(
+                "'{}' of '{name}' holds arrays of references, which cannot be combined \
+                 with batch, mask_dataset or order = 'F'",
+                dataset.name()
+            )
+            .into());
+        }
+        if let Some(references) = References::read(&dataset, &file_dtype, selection.as_ref())? {
+            return Ok(Self {
+                len: match &selection {
+                    Some(selection) => selection.len(),
+                    None => dataset_len(&dataset)?,
+                },
+                stream: None,
+                dtype: references.dtype,
+                data: Bytes::from(references.data).into(),
+                pending: None,
+                scale: None,
+                attrs,
+                nulls: vec![],
+                _items: references.items.into_iter().map(Bytes::from).collect(),
+            });
+        }
+        let mut dtype = match &options.columns {
+            Some(columns) => select_fields(&dataset, &file_dtype, columns, name)?,
+            None => datatype::to_descriptor(&file_dtype).map_err(|_| {
+                Hdf5ReadError::UnsupportedType {
+                    dataset: dataset.name(),
+                    field: None,
+                    dtype: datatype_name(&file_dtype),
+                }
+            })?,
+        };
+        if options.all_double {
+            dtype = widen_to_double(&dtype);
+        }
+        if !options.cast_to.is_empty() {
+            dtype = cast_columns(&dtype, &options.cast_to, name)?;
+        }
+        if let Some(len) = options.max_string_len {
+            dtype = shorten_strings(&dtype, len);
+        }
+        // Items of no size can't be told apart, e.g. with an empty `columns`.
+        if dtype.size() == 0 {
+            return Err(
+                format!("'{}' of '{name}' has a zero-size datatype", dataset.name()).into(),
+            );
+        }
+        let nulls = match options.nulls_from_fill {
+            true => missing_values(&dataset, &attr_names, &dtype, name)?,
+            false => vec![],
+        };
+        if stream
+            && has_vlen(&dtype)
+            && !has_reference_fields(&dtype)
+            && options.mask_dataset.is_none()
+            && options.hyperslab.is_empty()
+            && options.batch.is_none()
+            && (options.order == Order::C || dataset.ndim() < 2)
+            && dataset.ndim() > 0
+        {
+            return Ok(Self {
+                dtype: flatten_dtype(dtype.clone(), options.max_struct_fields),
+                data: Bytes::from(vec![]).into(),
+                pending: None,
+                len: dataset.size(),
+                stream: Some(VlenStream {
+                    swap: options.endian.needs_swap(file_dtype.byte_order()),
+                    dataset,
+                    dtype,
+                    prefetch: options.prefetch,
+                    reverse: options.reverse,
+                    worker: Default::default(),
+                }),
+                scale: None,
+                attrs,
+                nulls: vec![],
+                _items: vec![],
+            });
+        }
+        // Only whole datasets of a plain file on disk are mapped.
+        let mapping = if options.mmap
+            && options.driver == Driver::Sec2
+            && !options.image
+            && options.user_block_offset.is_none()
+            && !options.swmr
+            && selection.is_none()
+            && options.mask_dataset.is_none()
+            && options.batch.is_none()
+            && (options.order == Order::C || dataset.ndim() < 2)
+            && !options.endian.needs_swap(file_dtype.byte_order())
+            && !has_reference_fields(&dtype)
+        {
+            map_dataset(name, &dataset, &dtype)?
+        } else {
+            None
+        };
+        let read = ItemRead {
+            mask: options
+                .mask_dataset
+                .as_ref()
+                .map(|mask| open_dataset(file, mask, name))
+                .transpose()?,
+            selection,
+            swap: options.endian.needs_swap(file_dtype.byte_order()),
+            fortran: options.order == Order::Fortran,
+            squeeze: options.squeeze,
+            name: name.to_string(),
+            dtype: dtype.clone(),
+            dataset,
+        };
+        // Compounds with fields of references are read in bind, to resolve
+        // them to the paths they point to.
+        let mut items = vec![];
+        let mut resolved = None;
+        if has_reference_fields(&dtype) {
+            let data = Bytes::read(&dtype, read.read()?);
+            let fields = ReferenceFields::resolve(read.dataset.id(), &dtype, &data);
+            dtype = fields.dtype;
+            resolved = Some(Bytes::from(fields.data));
+            items.extend(fields.items.into_iter().map(Bytes::from));
+            items.push(data);
+        }
+        if let Some(size) = options.batch {
+            let data = match resolved {
+                Some(data) => data,
+                None => Bytes::read(&dtype, read.read()?),
+            };
+            let (dtype, rows) = batch(dtype, &data, &read.shape(), size);
+            items.push(data);
+            return Ok(Self {
+                len: rows.len() / std::mem::size_of::<hdf5_sys::h5t::hvl_t>(),
+                stream: None,
+                dtype,
+                data: Bytes::from(rows).into(),
+                pending: None,
+                scale: None,
+                attrs,
+                nulls: vec![],
+                _items: items,
+            });
+        }
+        // Masked items are counted by reading them.
+        let (data, pending) = match (mapping, resolved) {
+            (_, Some(data)) => (data.into(), None),
+            (Some(mapping), None) => (Bytes::Mapped(mapping).into(), None),
+            (None, None) if stream && read.mask.is_none() => (OnceLock::new(), Some(read)),
+            (None, None) => (Bytes::read(&dtype, read.read()?).into(), None),
+        };
+        let len = match (data.get(), &pending) {
+            (Some(data), _) => data.len() / dtype.size(),
+            (None, Some(read)) => read.len()?,
+            (None, None) => unreachable!(),
+        };
+        let numeric = matches!(
+            dtype,
+            TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) | TypeDescriptor::Float(_)
+        );
+        let scale = scale.filter(|_| numeric);
+        Ok(Self {
+            len,
+            stream: None,
+            dtype: flatten_dtype(dtype, options.max_struct_fields),
+            data,
+            pending,
+            scale,
+            attrs,
+            nulls,
+            _items: items,
+        })
+    }
+
+    /// The items, read on first use if they are pending.
+    fn items(&self) -> Result<&Bytes, Box<dyn Error>> {
+        if let Some(data) = self.data.get() {
+            return Ok(data);
+        }
+        let pending = self.pending.as_ref().unwrap();
+        let data = Bytes::read(&pending.dtype, pending.read()?);
+        Ok(self.data.get_or_init(|| data))
+    }
+}
+
+/// How to read the items of a dataset, deferred until they are scanned so
+/// that binding, e.g. of a view, only reads the metadata.
+struct ItemRead {
+    dataset: hdf5::Dataset,
+    dtype: TypeDescriptor,
+    selection: Option<Selection>,
+    mask: Option<hdf5::Dataset>,
+    /// Whether to swap the bytes of the items, with `endian`.
+    swap: bool,
+    /// Whether to reorder the items in column-major order.
+    fortran: bool,
+    /// Whether to drop the axes of size 1 of the dataset from the shape.
+    squeeze: bool,
+    /// The name of the file in errors.
+    name: String,
+}
+
+impl ItemRead {
+    /// The shape of the selected items, before a mask.
+    fn shape(&self) -> Vec<usize> {
+        let shape = match &self.selection {
+            Some(selection) => selection.count.iter().map(|c| *c as usize).collect(),
+            None => self.dataset.shape(),
+        };
+        if !self.squeeze {
+            return shape;
+        }
+        shape
+            .into_iter()
+            .zip(self.dataset.shape())
+            .filter(|(_, dim)| *dim != 1)
+            .map(|(count, _)| count)
+            .collect()
+    }
+
+    /// The number of selected items, before a mask.
+    fn len(&self) -> hdf5::Result<usize> {
+        match &self.selection {
+            Some(selection) => Ok(selection.len()),
+            None => dataset_len(&self.dataset),
+        }
+    }
+
+    /// Whether a failed read was caused by a Fletcher32 checksum mismatch:
+    /// HDF5 only reports a filter failure, so read the items again without
+    /// verifying the checksums.
+    fn is_checksum_mismatch(&self) -> bool {
+        let (dataset, dtype) = (&self.dataset, &self.dtype);
+        if !dataset.filters().contains(&Filter::Fletcher32) {
+            return false;
+        }
+        let read = || -> Result<(), Box<dyn Error>> {
+            let (file_space, len) = match (&self.mask, &self.selection) {
+                (Some(mask), _) => mask_space(dataset, mask)?,
+                (None, Some(selection)) => (selection.select(dataset)?, selection.len()),
+                (None, None) => (dataset.space()?, dataset_len(dataset)?),
+            };
+            let dxpl: hdf5::plist::PropertyList = unsafe {
+                hdf5::from_id(hdf5::h5call!(hdf5_sys::h5p::H5Pcreate(
+                    *hdf5::globals::H5P_DATASET_XFER
+                ))?)?
+            };
+            hdf5::h5call!(hdf5_sys::h5p::H5Pset_edc_check(
+                dxpl.id(),
+                hdf5_sys::h5z::H5Z_DISABLE_EDC
+            ))?;
+            let data = read_selected_with(dataset, &file_space, len, dtype, dxpl.id())?;
+            if has_vlen(dtype) {
+                drop(VlenItems::new(dtype, data));
+            }
+            Ok(())
+        };
+        read().is_ok()
+    }
+
+    fn read(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let (dataset, dtype) = (&self.dataset, &self.dtype);
+        let read = match (&self.mask, &self.selection) {
+            (Some(mask), _) => Ok(read_masked(dataset, mask, dtype)?),
+            (None, Some(selection)) => selection.read(dataset, dtype),
+            (None, None) => dataset.read_raw_bytes(dtype),
+        };
+        let mut data = match read {
+            Err(_) if self.is_checksum_mismatch() => {
+                return Err(format!(
+                    "checksum mismatch in '{}' of '{}'",
+                    dataset.name(),
+                    self.name
+                )
+                .into())
+            }
+            data => data?,
+        };
+        if self.swap {
+            for item in data.chunks_exact_mut(dtype.size()) {
+                swap_bytes(dtype, item);
+            }
+        }
+        let shape = self.shape();
+        if self.fortran && shape.len() > 1 {
+            data = to_column_major(&data, &shape, dtype.size());
+        }
+        Ok(data)
+    }
+}
+
+impl Hdf5ReadBindData {
+    fn new(
+        path: &str,
+        dataset: Option<&str>,
+        options: &Hdf5ReadOptions,
+    ) -> Result<Self, Box<dyn Error>> {
+        let paths = expand_path(path)?;
+        let mut files = vec![];
+        for path in &paths {
+            let file = open_file(path, options)?;
+            files.push(Hdf5FileData::read(&file, path, dataset, options, true)?);
+        }
+        let dtype = if options.union_by_name {
+            union_dtype(&files, &paths)?
+        } else {
+            match files
+                .iter()
+                .zip(&paths)
+                .find(|(f, _)| f.dtype != files[0].dtype)
+            {
+                Some((_, path)) => {
+                    return Err(
+                        format!("datatype of '{path}' does not match '{}'", paths[0]).into(),
+                    )
+                }
+                None => files[0].dtype.clone(),
+            }
+        };
+        for (i, attr) in options.attrs.iter().enumerate() {
+            let mut values = files
+                .iter()
+                .zip(&paths)
+                .filter_map(|(f, p)| Some((f.attrs[i].as_ref()?, p)));
+            if let Some((first, first_path)) = values.next() {
+                if let Some((_, path)) =
+                    values.find(|(v, _)| v.member_name() != first.member_name())
+                {
+                    return Err(format!(
+                        "datatype of attribute '{attr}' of '{path}' does not match '{first_path}'"
+                    )
+                    .into());
+                }
+            }
+        }
+        let hive_partitioning = options
+            .hive_partitioning
+            .unwrap_or_else(|| is_hive_partitioned(&paths[0]));
+        let partitions = if hive_partitioning {
+            Partition::parse(&paths)?
+        } else {
+            vec![]
+        };
+        Self {
+            dtype,
+            files,
+            attrs: options.attrs.clone(),
+            partitions,
+            column_names: options.column_names.clone(),
+            limits: Limits {
+                preview: options.preview,
+                max_string_len: options.max_string_len,
+            },
+            styles: vec![],
+            renames: options
+                .schema_hint
+                .iter()
+                .filter_map(|hint| Some((hint.column.clone(), hint.name.clone()?)))
+                .collect(),
+            float_special: options.float_special,
+            raw_compound: options.raw_compound,
+            with_index: options.with_index,
+            reverse: options.reverse,
+            timeout: options.timeout,
+        }
+        .check_column_names()?
+        .check_raw_compound()?
+        .with_styles(options)
+    }
+
+    /// Read a dataset of an in-memory file image.
+    fn from_image(
+        image: &[u8],
+        dataset: Option<&str>,
+        options: &Hdf5ReadOptions,
+    ) -> Result<Self, Box<dyn Error>> {
+        let file = open_image(image)?;
+        Self::from_data(
+            Hdf5FileData::read(&file, "blob", dataset, options, true)?,
+            options,
+        )
+    }
+
+    /// Read the items of a single file read before.
+    fn from_data(data: Hdf5FileData, options: &Hdf5ReadOptions) -> Result<Self, Box<dyn Error>> {
+        Self {
+            dtype: data.dtype.clone(),
+            files: vec![data],
+            attrs: options.attrs.clone(),
+            partitions: vec![],
+            column_names: options.column_names.clone(),
+            limits: Limits {
+                preview: options.preview,
+                max_string_len: options.max_string_len,
+            },
+            styles: vec![],
+            renames: options
+                .schema_hint
+                .iter()
+                .filter_map(|hint| Some((hint.column.clone(), hint.name.clone()?)))
+                .collect(),
+            float_special: options.float_special,
+            raw_compound: options.raw_compound,
+            with_index: options.with_index,
+            reverse: options.reverse,
+            timeout: options.timeout,
+        }
+        .check_column_names()?
+        .check_raw_compound()?
+        .with_styles(options)
+    }
+
+    /// The number of columns read from the dataset, before the attributes
+    /// and partitions.
+    fn data_columns(&self) -> usize {
+        if self.files[0].scale.is_some() {
+            1
+        } else {
+            iter_dtype(&self.dtype).len()
+        }
+    }
+
+    /// Check there is one of `column_names` per column of the dataset.
+    fn check_column_names(self) -> Result<Self, Box<dyn Error>> {
+        match &self.column_names {
+            Some(names) if names.len() != self.data_columns() => Err(format!(
+                "column_names has {} names, but the dataset has {} columns",
+                names.len(),
+                self.data_columns()
+            )
+            .into()),
+            _ => Ok(self),
+        }
+    }
+
+    /// Check `raw_compound` reads a compound dataset.
+    fn check_raw_compound(self) -> Result<Self, Box<dyn Error>> {
+        match &self.dtype {
+            TypeDescriptor::Compound(_) => Ok(self),
+            dtype if self.raw_compound => Err(format!(
+                "raw_compound requires a compound dataset, but the dataset is {}",
+                dtype_name(dtype)
+            )
+            .into()),
+            _ => Ok(self),
+        }
+    }
+
+    /// The types of the columns of the dataset.
+    fn column_dtypes(&self) -> Vec<&TypeDescriptor> {
+        match &self.dtype {
+            TypeDescriptor::Compound(c) => c.fields.iter().map(|f| &f.ty).collect(),
+            dtype => vec![dtype],
+        }
+    }
+
+    /// Set the styles of the columns from `bits_to_list`, `ubigint_as` and
+    /// `schema_hint`, checking the hints name columns of the dataset, and the
+    /// styles apply to integers.
+    fn with_styles(mut self, options: &Hdf5ReadOptions) -> Result<Self, Box<dyn Error>> {
+        let columns = iter_dtype(&self.dtype);
+        if let Some(hint) = options
+            .schema_hint
+            .iter()
+            .find(|hint| !columns.iter().any(|(column, _)| *column == hint.column))
+        {
+            return Err(format!("schema_hint column '{}' not found", hint.column).into());
+        }
+        let mut styles = vec![];
+        for ((column, _), dtype) in columns.iter().zip(self.column_dtypes()) {
+            let mark = options
+                .schema_hint
+                .iter()
+                .find(|hint| hint.column == *column)
+                .and_then(|hint| hint.mark.as_ref());
+            // The labels of an enum, or of the elements of a fixed array.
+            let enum_type = match dtype {
+                TypeDescriptor::FixedArray(item, _) => item.as_ref(),
+                dtype => dtype,
+            };
+            let (style, option) = match (mark, enum_type) {
+                (Some(Mark::Enum), TypeDescriptor::Enum(e)) => {
+                    let labels = e
+                        .members
+                        .iter()
+                        .map(|m| {
+                            (
+                                read_i64(enum_type, &m.value.to_ne_bytes()).unwrap(),
+                                m.name.clone(),
+                            )
+                        })
+                        .collect();
+                    styles.push(Some(Style::Labels(labels)));
+                    continue;
+                }
+                (Some(Mark::Enum), _) => {
+                    return Err(format!(
+                        "schema_hint enum without labels requires an HDF5 enum, \
+                         but '{column}' is {}",
+                        dtype_name(dtype)
+                    )
+                    .into())
+                }
+                (Some(Mark::Time(per_second)), _) => (Style::Time(*per_second), "schema_hint time"),
+                (Some(Mark::Labels(labels)), _) => {
+                    (Style::Labels(labels.clone()), "schema_hint enum")
+                }
+                (None, TypeDescriptor::Compound(c)) if options.compound_as == CompoundAs::Map => {
+                    let ty = &c.fields.first().map(|f| &f.ty);
+                    if ty.is_none() || c.fields.iter().any(|f| Some(&f.ty) != *ty) {
+                        return Err(format!(
+                            "compound_as = 'map' requires fields of a single type, \
+                             but '{column}' is {}",
+                            dtype_name(dtype)
+                        )
+                        .into());
+                    }
+                    styles.push(Some(Style::Map));
+                    continue;
+                }
+                (None, TypeDescriptor::Unsigned(IntSize::U8))
+                    if options.ubigint_as != UbigintAs::Ubigint =>
+                {
+                    // As with labels, fixed arrays are styled item by item.
+                    styles.push(Some(match options.ubigint_as {
+                        UbigintAs::Double => Style::Double,
+                        _ => Style::Digits,
+                    }));
+                    continue;
+                }
+                (None, _) if options.bits_to_list => (Style::Bits, "bits_to_list"),
+                (None, _) => {
+                    styles.push(None);
+                    continue;
+                }
+            };
+            if !matches!(
+                dtype,
+                TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_)
+            ) {
+                return Err(format!(
+                    "{option} requires integer columns, but '{column}' is {}",
+                    dtype_name(dtype)
+                )
+                .into());
+            }
+            styles.push(Some(style));
+        }
+        if self.files[0].scale.is_some() && styles.iter().any(Option::is_some) {
+            return Err("apply_scale cannot be combined with schema_hint time or enum".into());
+        }
+        self.styles = styles;
+        Ok(self)
+    }
+
+    fn iter_dtype(&self) -> Vec<(Cow<'static, str>, LogicalTypeHandle)> {
+        let mut columns = if self.files[0].scale.is_some() {
+            vec![(RESULT_COLNAME, LogicalTypeId::Double.into())]
+        } else {
+            iter_dtype(&self.dtype)
+        };
+        let dtypes = self.column_dtypes();
+        for (((_, ty), dtype), style) in columns.iter_mut().zip(dtypes).zip(&self.styles) {
+            if let Some(style) = style {
+                *ty = style.logical_type(dtype);
+            }
+        }
+        if let Some(k) = self.limits.preview {
+            let dtypes = self.column_dtypes().into_iter().zip(&self.styles);
+            // Arrays of labels are kept whole.
+            for ((_, ty), (dtype, _)) in columns
+                .iter_mut()
+                .zip(dtypes)
+                .filter(|(_, (_, s))| s.is_none())
+            {
+                if let TypeDescriptor::FixedArray(item, len) = dtype {
+                    if *len > k {
+                        *ty = LogicalTypeHandle::array(&logical_type(item), k as _);
+                    }
+                }
+            }
+        }
+        if self.raw_compound {
+            columns.push((Cow::Borrowed("__raw"), LogicalTypeId::Blob.into()));
+        }
+        if self.with_index {
+            columns.push((Cow::Borrowed("__index"), LogicalTypeId::UBigint.into()));
+        }
+        for (column, _) in &mut columns {
+            if let Some((_, name)) = self.renames.iter().find(|(c, _)| c == column) {
+                *column = Cow::Owned(name.clone());
+            }
+        }
+        if let Some(names) = &self.column_names {
+            for ((column, _), name) in columns.iter_mut().zip(names) {
+                *column = Cow::Owned(name.clone());
+            }
+        }
+        for (i, attr) in self.attrs.iter().enumerate() {
+            // Attributes missing from every file are NULL VARCHARs.
+            let dtype = self
+                .files
+                .iter()
+                .find_map(|f| f.attrs[i].as_ref())
+                .map_or_else(
+                    || LogicalTypeId::Varchar.into(),
+                    AttributeValue::logical_type,
+                );
+            columns.push((Cow::Owned(attr.clone()), dtype));
+        }
+        for p in &self.partitions {
+            columns.push((Cow::Owned(p.name.clone()), p.logical_type()));
+        }
+        let names = unique_names(columns.iter().map(|(name, _)| name.as_ref()));
+        columns
+            .into_iter()
+            .zip(names)
+            .map(|((_, dtype), name)| (Cow::Owned(name), dtype))
+            .collect()
+    }
+
+    fn project(&self, indices: &[duckdb::ffi::idx_t]) -> Hdf5ReadInitData {
+        let data_columns = self.data_columns();
+        let raw_columns = usize::from(self.raw_compound);
+        let extra_columns = raw_columns + usize::from(self.with_index);
+        let mut data = vec![];
+        let mut raw = None;
+        let mut index = None;
+        let mut attrs = vec![];
+        let mut partitions = vec![];
+        for (col, i) in indices.iter().enumerate() {
+            let i = *i as usize;
+            if i < data_columns {
+                data.push((col, i));
+            } else if i - data_columns < raw_columns {
+                raw = Some(col);
+            } else if i - data_columns < extra_columns {
+                index = Some(col);
+            } else if i - data_columns - extra_columns < self.attrs.len() {
+                attrs.push((col, i - data_columns - extra_columns));
+            } else if i - data_columns - extra_columns - self.attrs.len() < self.partitions.len() {
+                partitions.push((col, i - data_columns - extra_columns - self.attrs.len()));
+            }
+        }
+        let files = self
+            .files
+            .iter()
+            .map(|file| Projection::new(&self.dtype, &file.dtype, &data, &self.styles))
+            .collect();
+        // Unpacked values are doubles, whatever the stored type.
+        let dtypes = match self.files[0].scale {
+            Some(_) => vec![&TypeDescriptor::Float(FloatSize::U8)],
+            None => self.column_dtypes(),
+        };
+        let columns = self.iter_dtype();
+        let floats = data
+            .iter()
+            .filter(|(_, i)| self.styles.get(*i).is_none_or(Option::is_none))
+            .filter_map(|(col, i)| match dtypes[*i] {
+                TypeDescriptor::Float(size) => Some((*col, *size, columns[*i].0.to_string())),
+                _ => None,
+            })
+            .collect();
+        let starts = self
+            .file_lens()
+            .scan(0, |start, len| {
+                let file_start = *start;
+                *start += len;
+                Some(file_start)
+            })
+            .collect();
+        Hdf5ReadInitData {
+            chunk: AtomicUsize::new(0),
+            total: self.len(),
+            starts,
+            files,
+            raw,
+            index,
+            attrs,
+            partitions,
+            floats,
+            deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+
+    /// The number of items of every file.
+    fn file_lens(&self) -> impl Iterator<Item = usize> + '_ {
+        self.files.iter().map(|f| f.len)
+    }
+
+    /// The total number of rows.
+    fn len(&self) -> usize {
+        self.file_lens().sum()
+    }
+
+    /// Emit the next vector of rows: every call but the last fills a whole
+    /// vector, so the chunk boundaries only depend on the number of rows.
+    fn scan(
+        &self,
+        init_data: &Hdf5ReadInitData,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let capacity = unsafe { ffi::duckdb_vector_size() } as usize;
+        let chunk = init_data.chunk.fetch_add(1, Ordering::Relaxed);
+        let (start, count) = vector_rows(chunk, capacity, init_data.total);
+        // HDF5 reads can't be interrupted, so a vector whose reads end past
+        // the deadline fails after them.
+        self.check_deadline(init_data)?;
+        self.fill(start, count, init_data, output)?;
+        self.check_deadline(init_data)
+    }
+
+    /// Fail if the scan has taken longer than `timeout_ms`.
+    fn check_deadline(&self, init_data: &Hdf5ReadInitData) -> Result<(), Box<dyn Error>> {
+        match (init_data.deadline, self.timeout) {
+            (Some(deadline), Some(timeout)) if Instant::now() > deadline => Err(format!(
+                "read_hdf5 timed out, as the scan took more than timeout_ms = {}",
+                timeout.as_millis()
+            )
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn fill(
+        &self,
+        start: usize,
+        count: usize,
+        init_data: &Hdf5ReadInitData,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        // The rows of a vector within one file read into memory are filled
+        // column by column.
+        let first = init_data.starts.partition_point(|s| *s <= start) - 1;
+        let by_column = count > 0
+            && init_data.starts.partition_point(|s| *s < start + count) - 1 == first
+            && self.files[first].stream.is_none()
+            && self.files[first].scale.is_none()
+            && !self.reverse;
+        if by_column {
+            let Hdf5FileData { dtype, .. } = &self.files[first];
+            let data = self.files[first].items()?;
+            let item_size = dtype.size();
+            let item = start - init_data.starts[first];
+            let data = &data[item * item_size..(item + count) * item_size];
+            let projection = &init_data.files[first];
+            let column = |col: usize| unsafe {
+                ffi::duckdb_data_chunk_get_vector(output.get_ptr(), col as _)
+            };
+            match &projection.dtype {
+                TypeDescriptor::Compound(c) => {
+                    let columns = projection.columns.iter().zip(&projection.styles);
+                    for (f, (col, style)) in c.fields.iter().zip(columns) {
+                        let data = &data[f.offset..];
+                        let (vector, style) = (column(*col), style.as_ref());
+                        fill_column(&f.ty, data, item_size, count, vector, self.limits, style);
+                    }
+                }
+                dtype => {
+                    if let (Some(col), Some(style)) =
+                        (projection.columns.first(), projection.styles.first())
+                    {
+                        let (vector, style) = (column(*col), style.as_ref());
+                        fill_column(dtype, data, item_size, count, vector, self.limits, style);
+                    }
+                }
+            }
+        }
+        // The items of the streamed file of the current row, reclaimed when
+        // the next file starts or the vector is filled.
+        let mut batch: Option<(usize, VlenBatch)> = None;
+        for row in 0..count {
+            let index = match self.reverse {
+                true => init_data.total - 1 - (start + row),
+                false => start + row,
+            };
+            let file = init_data.starts.partition_point(|s| *s <= index) - 1;
+            let Hdf5FileData {
+                dtype,
+                len,
+                stream,
+                scale,
+                attrs,
+                nulls,
+                ..
+            } = &self.files[file];
+            let item_size = dtype.size();
+            let item = index - init_data.starts[file];
+            let projection = &init_data.files[file];
+            let data = match stream {
+                // Nothing is read when no column of the dataset is projected,
+                // as with `count(*)`.
+                Some(_) if projection.columns.is_empty() && init_data.raw.is_none() => &[][..],
+                Some(stream) => {
+                    if !matches!(&batch, Some((f, _)) if *f == file) {
+                        // Reclaim the previous batch before reading the next.
+                        drop(batch.take());
+                        // Reversed, the batch ends at the current item.
+                        let (batch_start, batch_len) = match self.reverse {
+                            true => {
+                                let batch_len = (count - row).min(item + 1);
+                                (item + 1 - batch_len, batch_len)
+                            }
+                            false => (item, (count - row).min(len - item)),
+                        };
+                        batch = Some((file, stream.read(batch_start, batch_len)?));
+                    }
+                    let (_, batch) = batch.as_ref().unwrap();
+                    &batch.data[(item - batch.start) * item_size..][..item_size]
+                }
+                None => &self.files[file].items()?[item * item_size..][..item_size],
+            };
+            match (scale, projection.columns.first()) {
+                _ if by_column => {}
+                (Some(scale), Some(col)) => {
+                    let value = read_f64(dtype, data).unwrap();
+                    let mut vector = output.flat_vector(*col);
+                    vector.as_mut_slice::<f64>()[row] = value * scale.factor + scale.offset;
+                }
+                _ => fill_row(
+                    &projection.dtype,
+                    data,
+                    output,
+                    &projection.columns,
+                    &projection.styles,
+                    row,
+                    self.limits,
+                ),
+            }
+            if let Some(col) = projection.columns.first() {
+                if nulls.iter().any(|value| value.as_slice() == data) {
+                    output.flat_vector(*col).set_null(row);
+                }
+            }
+            for col in &projection.missing {
+                let vector =
+                    unsafe { ffi::duckdb_data_chunk_get_vector(output.get_ptr(), *col as _) };
+                set_null_nested(vector, row);
+            }
+            if let Some(col) = init_data.raw {
+                output.flat_vector(col).insert(row, data);
+            }
+            for (col, a) in &init_data.attrs {
+                let vector =
+                    unsafe { ffi::duckdb_data_chunk_get_vector(output.get_ptr(), *col as _) };
+                match &attrs[*a] {
+                    Some(value) => value.fill(vector, row),
+                    None => FlatVector::from(vector).set_null(row),
+                }
+            }
+            for (col, p) in &init_data.partitions {
+                let vector =
+                    unsafe { ffi::duckdb_data_chunk_get_vector(output.get_ptr(), *col as _) };
+                self.partitions[*p].fill(file, vector, row);
+            }
+        }
+        // The position of a row only depends on the vector it is in, whichever
+        // thread fills it.
+        if let Some(col) = init_data.index {
+            let mut vector = output.flat_vector(col);
+            for (row, index) in vector.as_mut_slice::<u64>()[..count].iter_mut().enumerate() {
+                *index = (start + row) as u64;
+            }
+        }
+        self.check_floats(start, count, init_data, output)?;
+        output.set_len(count);
+        Ok(())
+    }
+
+    /// Apply `float_special` to the NaN and infinite floats of the vector of
+    /// rows from `start`.
+    fn check_floats(
+        &self,
+        start: usize,
+        count: usize,
+        init_data: &Hdf5ReadInitData,
+        output: &mut DataChunkHandle,
+    ) -> hdf5::Result<()> {
+        if self.float_special == FloatSpecial::Keep {
+            return Ok(());
+        }
+        for (col, size, name) in &init_data.floats {
+            let mut vector = output.flat_vector(*col);
+            for row in 0..count {
+                let finite = match size {
+                    FloatSize::U4 => vector.as_slice::<f32>()[row].is_finite(),
+                    FloatSize::U8 => vector.as_slice::<f64>()[row].is_finite(),
+                };
+                if finite || vector.row_is_null(row as _) {
+                    continue;
+                }
+                match self.float_special {
+                    FloatSpecial::Null => vector.set_null(row),
+                    _ => {
+                        return Err(format!(
+                            "column '{name}' has a NaN or infinite value at row {}",
+                            start + row
+                        )
+                        .into())
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Set `row` of `vector` NULL, with the fields of a struct and the items of
+/// an array, whose validity DuckDB reads apart from that of the row.
+fn set_null_nested(vector: ffi::duckdb_vector, row: usize) {
+    let mut ty = unsafe { ffi::duckdb_vector_get_column_type(vector) };
+    match unsafe { ffi::duckdb_get_type_id(ty) } {
+        ffi::DUCKDB_TYPE_DUCKDB_TYPE_STRUCT => {
+            for i in 0..unsafe { ffi::duckdb_struct_type_child_count(ty) } {
+                set_null_nested(
+                    unsafe { ffi::duckdb_struct_vector_get_child(vector, i) },
+                    row,
+                );
+            }
+        }
+        ffi::DUCKDB_TYPE_DUCKDB_TYPE_ARRAY => {
+            let len = unsafe { ffi::duckdb_array_type_array_size(ty) } as usize;
+            let child = unsafe { ffi::duckdb_array_vector_get_child(vector) };
+            for i in row * len..(row + 1) * len {
+                set_null_nested(child, i);
+            }
+        }
+        ffi::DUCKDB_TYPE_DUCKDB_TYPE_LIST | ffi::DUCKDB_TYPE_DUCKDB_TYPE_MAP => {
+            // A NULL list has no items.
+            let entries =
+                unsafe { ffi::duckdb_vector_get_data(vector) }.cast::<ffi::duckdb_list_entry>();
+            unsafe {
+                entries.add(row).write(ffi::duckdb_list_entry {
+                    offset: 0,
+                    length: 0,
+                })
+            };
+        }
+        _ => {}
+    }
+    unsafe { ffi::duckdb_destroy_logical_type(&mut ty) };
+    FlatVector::from(vector).set_null(row);
+}
+
+/// The projected fields of a file, whose dtype may lack some fields of the
+/// result with `union_by_name`.
+struct Projection {
+    dtype: TypeDescriptor,
+    /// The output columns of the fields of `dtype`, and their styles.
+    columns: Vec<usize>,
+    styles: Vec<Option<Style>>,
+    /// The output columns of fields missing from the file.
+    missing: Vec<usize>,
+}
+
+impl Projection {
+    /// Project the `data` columns, pairs of an output column and a column of
+    /// `dtype` with the `styles` of the columns, onto the fields of
+    /// `file_dtype` with the same names.
+    fn new(
+        dtype: &TypeDescriptor,
+        file_dtype: &TypeDescriptor,
+        data: &[(usize, usize)],
+        styles: &[Option<Style>],
+    ) -> Self {
+        let (TypeDescriptor::Compound(c), TypeDescriptor::Compound(file_c)) = (dtype, file_dtype)
+        else {
+            return Self {
+                dtype: file_dtype.clone(),
+                columns: data.iter().map(|(col, _)| *col).collect(),
+                styles: data.iter().map(|(_, i)| styles[*i].clone()).collect(),
+                missing: vec![],
+            };
+        };
+        let mut fields = vec![];
+        let mut columns = vec![];
+        let mut column_styles = vec![];
+        let mut missing = vec![];
+        for (col, i) in data {
+            // Flattened fields may share a name, so match the same occurrence.
+            let name = &c.fields[*i].name;
+            let nth = c.fields[..*i].iter().filter(|f| f.name == *name).count();
+            match file_c.fields.iter().filter(|f| f.name == *name).nth(nth) {
+                Some(field) => {
+                    fields.push(field.clone());
+                    columns.push(*col);
+                    column_styles.push(styles[*i].clone());
+                }
+                None => missing.push(*col),
+            }
+        }
+        Self {
+            dtype: TypeDescriptor::Compound(CompoundType {
+                fields,
+                size: file_c.size,
+            }),
+            columns,
+            styles: column_styles,
+            missing,
+        }
+    }
+}
+
+struct Hdf5ReadInitData {
+    /// The index of the next vector to emit.
+    chunk: AtomicUsize,
+    /// The total number of rows.
+    total: usize,
+    /// The index of the first row of each file.
+    starts: Vec<usize>,
+    /// The projected fields of each file.
+    files: Vec<Projection>,
+    /// The output column of the bytes of each item, with `raw_compound`.
+    raw: Option<usize>,
+    /// The output column of the position of each row, with `with_index`.
+    index: Option<usize>,
+    /// The output columns of the projected attributes.
+    attrs: Vec<(usize, usize)>,
+    /// The output columns of the projected partitions.
+    partitions: Vec<(usize, usize)>,
+    /// The output columns of the projected floats, with their sizes and
+    /// names, checked for `float_special`.
+    floats: Vec<(usize, FloatSize, String)>,
+    /// The time the scan must end by, with `timeout_ms`.
+    deadline: Option<Instant>,
+}
+
+struct Hdf5Read;
+
+impl VTab for Hdf5Read {
+    type InitData = Hdf5ReadInitData;
+    type BindData = Hdf5ReadBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        // A NULL dataset selects the only dataset of the file.
+        let dataset = Some(bind.get_parameter(1))
+            .filter(|d| !is_null(d))
+            .map(|d| d.to_string());
+        let options = Hdf5ReadOptions::from_bind(bind)?;
+        let data = Hdf5ReadBindData::new(&path, dataset.as_deref(), &options)?;
+        for (name, dtype) in data.iter_dtype() {
+            bind.add_result_column(&name, dtype);
+        }
+        bind.set_cardinality(data.len() as _, true);
+        Ok(data)
+    }
+
+    fn init(init: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        let bind_data = unsafe { init.get_bind_data::<Self::BindData>().as_ref() }.unwrap();
+        Ok(bind_data.project(&init.get_column_indices()))
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        bind_data.scan(init_data, output)?;
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+
+    fn named_parameters() -> Option<Vec<(String, LogicalTypeHandle)>> {
+        let indices = || LogicalTypeHandle::list(&LogicalTypeId::UBigint.into());
+        Some(vec![
+            ("endian".to_string(), LogicalTypeId::Varchar.into()),
+            (
+                "hive_partitioning".to_string(),
+                LogicalTypeId::Boolean.into(),
+            ),
+            ("apply_scale".to_string(), LogicalTypeId::Boolean.into()),
+            (
+                "max_struct_fields".to_string(),
+                LogicalTypeId::Bigint.into(),
+            ),
+            ("all_double".to_string(), LogicalTypeId::Boolean.into()),
+            (
+                "cast_to".to_string(),
+                LogicalTypeHandle::map(
+                    &LogicalTypeId::Varchar.into(),
+                    &LogicalTypeId::Varchar.into(),
+                ),
+            ),
+            ("offset".to_string(), indices()),
+            ("count".to_string(), indices()),
+            ("stride".to_string(), indices()),
+            ("image".to_string(), LogicalTypeId::Boolean.into()),
+            (
+                "attrs".to_string(),
+                LogicalTypeHandle::list(&LogicalTypeId::Varchar.into()),
+            ),
+            ("attrs_optional".to_string(), LogicalTypeId::Boolean.into()),
+            ("union_by_name".to_string(), LogicalTypeId::Boolean.into()),
+            ("deref_regions".to_string(), LogicalTypeId::Boolean.into()),
+            ("deref".to_string(), LogicalTypeId::Boolean.into()),
+            ("swmr".to_string(), LogicalTypeId::Boolean.into()),
+            ("mask_dataset".to_string(), LogicalTypeId::Varchar.into()),
+            (
+                "columns".to_string(),
+                LogicalTypeHandle::list(&LogicalTypeId::Varchar.into()),
+            ),
+            (
+                "column_names".to_string(),
+                LogicalTypeHandle::list(&LogicalTypeId::Varchar.into()),
+            ),
+            (
+                "chunk_cache_bytes".to_string(),
+                LogicalTypeId::UBigint.into(),
+            ),
+            ("batch".to_string(), LogicalTypeId::UBigint.into()),
+            ("driver".to_string(), LogicalTypeId::Varchar.into()),
+            ("member_size".to_string(), LogicalTypeId::UBigint.into()),
+            ("alignment".to_string(), LogicalTypeId::UBigint.into()),
+            ("prefetch".to_string(), LogicalTypeId::Boolean.into()),
+            ("mmap".to_string(), LogicalTypeId::Boolean.into()),
+            ("preview".to_string(), LogicalTypeId::Boolean.into()),
+            ("preview_k".to_string(), LogicalTypeId::UBigint.into()),
+            ("bits_to_list".to_string(), LogicalTypeId::Boolean.into()),
+            ("schema_hint".to_string(), LogicalTypeId::Varchar.into()),
+            ("float_special".to_string(), LogicalTypeId::Varchar.into()),
+            ("order".to_string(), LogicalTypeId::Varchar.into()),
+            ("raw_compound".to_string(), LogicalTypeId::Boolean.into()),
+            ("max_string_len".to_string(), LogicalTypeId::UBigint.into()),
+            ("with_index".to_string(), LogicalTypeId::Boolean.into()),
+            ("reverse".to_string(), LogicalTypeId::Boolean.into()),
+            ("nulls_from_fill".to_string(), LogicalTypeId::Boolean.into()),
+            ("timeout_ms".to_string(), LogicalTypeId::UBigint.into()),
+            ("squeeze".to_string(), LogicalTypeId::Boolean.into()),
+            (
+                "user_block_offset".to_string(),
+                LogicalTypeId::UBigint.into(),
+            ),
+            ("compound_as".to_string(), LogicalTypeId::Varchar.into()),
+            ("ubigint_as".to_string(), LogicalTypeId::Varchar.into()),
+        ])
+    }
+
+    fn supports_pushdown() -> bool {
+        true
+    }
+}
+
+#[duckdb_entrypoint_c_api()]
 pub fn extension_entrypoint(con: Connection) -> Result<(), Box<dyn Error>> {
     con.register_table_function::<Hdf5Read>("read_hdf5")?;
+    con.register_table_function::<Hdf5ReadBlob>("read_hdf5_blob")?;
+    con.register_table_function::<Hdf5ReadPacketTable>("read_hdf5_packet_table")?;
+    con.register_table_function::<Hdf5ReadRecord>("read_hdf5_record")?;
+    con.register_table_function::<Hdf5ReadNexus>("read_nexus")?;
+    con.register_table_function::<Hdf5ReadLong>("read_hdf5_long")?;
+    con.register_table_function::<Hdf5Head>("hdf5_head")?;
+    con.register_table_function::<Hdf5Tail>("hdf5_tail")?;
+    con.register_table_function::<Hdf5Attributes>("read_hdf5_attributes")?;
+    con.register_table_function::<Hdf5GroupAttributes>("read_hdf5_group_attrs")?;
+    con.register_table_function::<Hdf5AllAttributes>("read_hdf5_all_attributes")?;
+    con.register_table_function::<Hdf5DatasetInfo>("hdf5_dataset_info")?;
+    con.register_table_function::<Hdf5Diff>("hdf5_diff")?;
+    con.register_table_function::<Hdf5ColumnStats>("hdf5_column_stats")?;
+    con.register_table_function::<Hdf5ReadJson>("read_hdf5_json")?;
+    con.register_table_function::<Hdf5Chunks>("read_hdf5_chunks")?;
+    con.register_table_function::<Hdf5Chunk>("read_hdf5_chunk")?;
+    con.register_table_function::<Hdf5ReadMeta>("read_hdf5_meta")?;
+    con.register_table_function::<Hdf5EosStructure>("read_hdfeos_structure")?;
+    con.register_scalar_function::<Hdf5Dtype>("hdf5_dtype")?;
+    con.register_scalar_function::<Hdf5ReadBase64>("hdf5_read_base64")?;
+    con.register_scalar_function::<Hdf5Readlink>("hdf5_readlink")?;
+    con.register_scalar_function::<Hdf5Version>("hdf5_version")?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::vector_rows;
+
+    #[test]
+    fn vector_rows_of_a_remainder() {
+        let rows: Vec<_> = (0..4).map(|chunk| vector_rows(chunk, 2048, 5000)).collect();
+        assert_eq!(rows, [(0, 2048), (2048, 2048), (4096, 904), (5000, 0)]);
+    }
+
+    #[test]
+    fn vector_rows_of_whole_vectors() {
+        let rows: Vec<_> = (0..3).map(|chunk| vector_rows(chunk, 2048, 4096)).collect();
+        assert_eq!(rows, [(0, 2048), (2048, 2048), (4096, 0)]);
+        assert_eq!(vector_rows(0, 2048, 0), (0, 0));
+        assert_eq!(vector_rows(0, 2048, 5), (0, 5));
+    }
+}
@@ -0,0 +1,197 @@
+use super::{
+    check_szip, datatype, dtype_name, mmap::Bytes, open_dataset, open_path, parse_names,
+    Hdf5FileData, Hdf5ReadBindData, Hdf5ReadInitData, Hdf5ReadOptions, ReadRawBytes,
+};
+use duckdb::{
+    core::{DataChunkHandle, LogicalTypeHandle, LogicalTypeId},
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use hdf5::types::{
+    CompoundField, CompoundType, FloatSize, IntSize, TypeDescriptor, VarLenAscii, VarLenUnicode,
+};
+use std::error::Error;
+
+/// The type the items of `dtype` are read as in the `value` column: `DOUBLE`
+/// for numbers, `VARCHAR` for strings.
+fn value_type(dtype: &TypeDescriptor) -> Option<TypeDescriptor> {
+    match dtype {
+        TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) | TypeDescriptor::Float(_) => {
+            Some(TypeDescriptor::Float(FloatSize::U8))
+        }
+        TypeDescriptor::FixedAscii(_)
+        | TypeDescriptor::FixedUnicode(_)
+        | TypeDescriptor::VarLenAscii
+        | TypeDescriptor::VarLenUnicode => Some(TypeDescriptor::VarLenUnicode),
+        _ => None,
+    }
+}
+
+/// The strings of `data`, items of the string type `dtype`, each followed by
+/// a NUL.
+fn read_strings(dtype: &TypeDescriptor, data: &[u8]) -> Vec<Vec<u8>> {
+    data.chunks_exact(dtype.size())
+        .map(|item| {
+            let mut string = match dtype {
+                TypeDescriptor::VarLenAscii => {
+                    let string = unsafe { item.as_ptr().cast::<VarLenAscii>().as_ref() }.unwrap();
+                    string.as_bytes().to_vec()
+                }
+                TypeDescriptor::VarLenUnicode => {
+                    let string = unsafe { item.as_ptr().cast::<VarLenUnicode>().as_ref() }.unwrap();
+                    string.as_bytes().to_vec()
+                }
+                _ => {
+                    let end = item.iter().position(|b| *b == 0).unwrap_or(item.len());
+                    item[..end].to_vec()
+                }
+            };
+            string.push(0);
+            string
+        })
+        .collect()
+}
+
+/// Read the `datasets` of the file at `path` into a long table, a row per
+/// item with the name of its dataset, its index in the dataset in row-major
+/// order, and its value. The datasets must be all of numbers or all of
+/// strings.
+fn read_long(path: &str, datasets: &[String]) -> Result<Hdf5FileData, Box<dyn Error>> {
+    let file = open_path(path)?;
+    let mut value_dtype: Option<(TypeDescriptor, &str)> = None;
+    let mut columns = vec![];
+    for name in datasets {
+        let dataset = open_dataset(&file, name, path)?;
+        check_szip(&dataset, path)?;
+        let dtype = datatype::to_descriptor(&dataset.dtype()?)?;
+        let Some(ty) = value_type(&dtype) else {
+            return Err(format!(
+                "read_hdf5_long requires datasets of numbers or strings, \
+                 but '{}' of '{path}' is {}",
+                dataset.name(),
+                dtype_name(&dtype)
+            )
+            .into());
+        };
+        match &value_dtype {
+            Some((first_ty, first)) if *first_ty != ty => {
+                return Err(format!(
+                    "read_hdf5_long requires datasets of numbers or of strings alike, \
+                     but '{first}' and '{name}' of '{path}' are not"
+                )
+                .into())
+            }
+            Some(_) => {}
+            None => value_dtype = Some((ty.clone(), name)),
+        }
+        // HDF5 converts numbers to doubles, but strings are read as stored.
+        let read_dtype = match ty {
+            TypeDescriptor::Float(_) => ty,
+            _ => dtype.clone(),
+        };
+        let data = Bytes::read(&read_dtype, dataset.read_raw_bytes(&read_dtype)?);
+        columns.push((name, dtype, data));
+    }
+    let Some((value_dtype, _)) = value_dtype else {
+        return Err("datasets must not be empty".into());
+    };
+
+    let fields = vec![
+        CompoundField::new("dataset", TypeDescriptor::VarLenUnicode, 0, 0),
+        CompoundField::new("index", TypeDescriptor::Integer(IntSize::U8), 8, 1),
+        CompoundField::new("value", value_dtype.clone(), 16, 2),
+    ];
+    let size = 24;
+    let mut data = vec![];
+    let mut items = vec![];
+    for (name, dtype, values) in &columns {
+        let mut name = name.as_bytes().to_vec();
+        name.push(0);
+        let pointer = |item: &[u8]| (item.as_ptr() as usize).to_ne_bytes();
+        let name_pointer = pointer(&name);
+        let values: Vec<[u8; 8]> = match value_dtype {
+            TypeDescriptor::Float(_) => values
+                .chunks_exact(8)
+                .map(|value| value.try_into().unwrap())
+                .collect(),
+            _ => {
+                let strings = read_strings(dtype, values);
+                let pointers = strings.iter().map(|s| pointer(s)).collect();
+                // Moving the buffers keeps their bytes in place.
+                items.extend(strings.into_iter().map(Bytes::from));
+                pointers
+            }
+        };
+        for (index, value) in values.iter().enumerate() {
+            data.extend_from_slice(&name_pointer);
+            data.extend_from_slice(&(index as i64).to_ne_bytes());
+            data.extend_from_slice(value);
+        }
+        items.push(Bytes::from(name));
+    }
+    Ok(Hdf5FileData {
+        len: data.len() / size,
+        dtype: TypeDescriptor::Compound(CompoundType { fields, size }),
+        data: Bytes::from(data).into(),
+        pending: None,
+        stream: None,
+        scale: None,
+        attrs: vec![],
+        nulls: vec![],
+        _items: items,
+    })
+}
+
+/// `read_hdf5_long(path, datasets := [...])`: the items of several datasets
+/// in a long table, with the name of the dataset and the index of each.
+pub(super) struct Hdf5ReadLong;
+
+impl VTab for Hdf5ReadLong {
+    type InitData = Hdf5ReadInitData;
+    type BindData = Hdf5ReadBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let datasets = bind
+            .get_named_parameter("datasets")
+            .map(|datasets| parse_names("datasets", &datasets.to_string()))
+            .transpose()?
+            .ok_or("read_hdf5_long requires datasets")?;
+        let options = Hdf5ReadOptions::from_bind(bind)?;
+        let data = Hdf5ReadBindData::from_data(read_long(&path, &datasets)?, &options)?;
+        for (name, dtype) in data.iter_dtype() {
+            bind.add_result_column(&name, dtype);
+        }
+        bind.set_cardinality(data.len() as _, true);
+        Ok(data)
+    }
+
+    fn init(init: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        let bind_data = unsafe { init.get_bind_data::<Self::BindData>().as_ref() }.unwrap();
+        Ok(bind_data.project(&init.get_column_indices()))
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        bind_data.scan(init_data, output)?;
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![LogicalTypeId::Varchar.into()])
+    }
+
+    fn named_parameters() -> Option<Vec<(String, LogicalTypeHandle)>> {
+        Some(vec![(
+            "datasets".to_string(),
+            LogicalTypeHandle::list(&LogicalTypeId::Varchar.into()),
+        )])
+    }
+
+    fn supports_pushdown() -> bool {
+        true
+    }
+}
@@ -0,0 +1,144 @@
+use super::{
+    attributes::write_attributes_json, info::Hdf5DatasetInfoBindData, json::write_string,
+    object_key, open_path,
+};
+use duckdb::{
+    core::{DataChunkHandle, Inserter, LogicalTypeHandle, LogicalTypeId},
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use std::{
+    error::Error,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The names of the links of `group` to objects of `loc_type`, in the order
+/// of `Group::groups` and `Group::datasets`.
+fn member_names_of_type(
+    group: &hdf5::Group,
+    loc_type: hdf5::LocationType,
+) -> hdf5::Result<Vec<String>> {
+    group.iter_visit_default(vec![], |group, name, _, names| {
+        match group.loc_info_by_name(name) {
+            Ok(info) => {
+                if info.loc_type == loc_type {
+                    names.push(name.to_owned());
+                }
+                true
+            }
+            Err(_) => false,
+        }
+    })
+}
+
+/// The path of the link `name` of the group at `path`.
+fn member_path(path: &str, name: &str) -> String {
+    format!("{}/{name}", path.trim_end_matches('/'))
+}
+
+/// Append `group`, linked at `path`, to `json` as an object with its
+/// attributes, datasets and groups, recursively. Only metadata is read, never
+/// the items of datasets. A group linked again below itself, through a hard
+/// or soft link, is written as its `name` and the path of the `ancestors`
+/// entry it links to, so that cycles end. Groups linked from elsewhere are
+/// written in full at each path.
+fn write_group(
+    json: &mut String,
+    group: &hdf5::Group,
+    path: &str,
+    ancestors: &mut Vec<(String, String)>,
+) -> hdf5::Result<()> {
+    json.push_str("{\"name\":");
+    write_string(json, path);
+    json.push_str(",\"attributes\":");
+    write_attributes_json(json, group)?;
+    json.push_str(",\"datasets\":[");
+    let datasets = member_names_of_type(group, hdf5::LocationType::Dataset)?;
+    for (i, name) in datasets.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        let dataset = group.dataset(name)?;
+        json.push_str("{\"name\":");
+        write_string(json, &member_path(path, name));
+        json.push(',');
+        Hdf5DatasetInfoBindData::from_dataset(&dataset)?.write_json_fields(json);
+        json.push_str(",\"attributes\":");
+        write_attributes_json(json, &dataset)?;
+        json.push('}');
+    }
+    json.push_str("],\"groups\":[");
+    let groups = member_names_of_type(group, hdf5::LocationType::Group)?;
+    for (i, name) in groups.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        let child = group.group(name)?;
+        let child_path = member_path(path, name);
+        let key = object_key(&child)?;
+        if let Some((_, ancestor)) = ancestors.iter().find(|(k, _)| *k == key) {
+            json.push_str("{\"name\":");
+            write_string(json, &child_path);
+            json.push_str(",\"link_to\":");
+            write_string(json, ancestor);
+            json.push('}');
+            continue;
+        }
+        ancestors.push((key, child_path.clone()));
+        write_group(json, &child, &child_path, ancestors)?;
+        ancestors.pop();
+    }
+    json.push_str("]}");
+    Ok(())
+}
+
+pub struct Hdf5ReadMetaBindData {
+    json: String,
+}
+
+pub struct Hdf5ReadMetaInitData {
+    done: AtomicBool,
+}
+
+/// `read_hdf5_meta(path)`: the hierarchy of a file as one JSON document.
+pub(super) struct Hdf5ReadMeta;
+
+impl VTab for Hdf5ReadMeta {
+    type InitData = Hdf5ReadMetaInitData;
+    type BindData = Hdf5ReadMetaBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let file = open_path(&path)?;
+        let mut json = String::new();
+        let mut ancestors = vec![(object_key(&file)?, "/".to_string())];
+        write_group(&mut json, &file, "/", &mut ancestors)?;
+        bind.add_result_column("json", LogicalTypeId::Varchar.into());
+        bind.set_cardinality(1, true);
+        Ok(Hdf5ReadMetaBindData { json })
+    }
+
+    fn init(_: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Ok(Hdf5ReadMetaInitData {
+            done: AtomicBool::new(false),
+        })
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        if func.get_init_data().done.swap(true, Ordering::Relaxed) {
+            output.set_len(0);
+        } else {
+            output
+                .flat_vector(0)
+                .insert(0, func.get_bind_data().json.as_str());
+            output.set_len(1);
+        }
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![LogicalTypeId::Varchar.into()])
+    }
+}
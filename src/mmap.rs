@@ -0,0 +1,136 @@
+use super::{datatype, has_vlen, stream::VlenItems};
+use hdf5::{plist::dataset_create::Layout, types::TypeDescriptor};
+use hdf5_sys::{h5, h5d, h5t};
+use std::{fs::File, ops::Deref, os::fd::AsRawFd};
+
+/// A read-only mapping of a byte range of a file.
+pub struct Mapping {
+    /// The start of the mapping, page aligned.
+    ptr: *mut libc::c_void,
+    map_len: usize,
+    /// The range within the mapping.
+    start: usize,
+    len: usize,
+}
+
+// The mapping is never written.
+unsafe impl Send for Mapping {}
+unsafe impl Sync for Mapping {}
+
+impl Mapping {
+    fn new(file: &File, offset: u64, len: usize) -> std::io::Result<Self> {
+        let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+        let map_offset = offset / page * page;
+        let start = (offset - map_offset) as usize;
+        let map_len = start + len;
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                map_len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                map_offset as _,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Self {
+            ptr,
+            map_len,
+            start,
+            len,
+        })
+    }
+}
+
+impl Deref for Mapping {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.cast::<u8>().add(self.start), self.len) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr, self.map_len) };
+    }
+}
+
+/// The items of a dataset, read into memory or mapped from the file.
+pub enum Bytes {
+    Owned(Vec<u8>),
+    Mapped(Mapping),
+    /// Items with variable-length payloads allocated by HDF5.
+    Vlen(VlenItems),
+}
+
+impl Bytes {
+    /// The items read by HDF5 as `dtype`, reclaiming their payloads if any.
+    pub fn read(dtype: &TypeDescriptor, data: Vec<u8>) -> Self {
+        match has_vlen(dtype) {
+            true => Self::Vlen(VlenItems::new(dtype, data)),
+            false => Self::Owned(data),
+        }
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Owned(data)
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Owned(data) => data,
+            Self::Mapped(mapping) => mapping,
+            Self::Vlen(items) => items,
+        }
+    }
+}
+
+/// Map the items of `dataset` in the file at `path`, if they are stored
+/// contiguously in the file exactly as `dtype` in memory. `None` if they
+/// have to be read by HDF5 instead.
+pub fn map_dataset(
+    path: &str,
+    dataset: &hdf5::Dataset,
+    dtype: &TypeDescriptor,
+) -> Result<Option<Mapping>, Box<dyn std::error::Error>> {
+    // Variable-length items are stored in the global heap.
+    if dataset.layout() != Layout::Contiguous || has_vlen(dtype) || dtype.size() == 0 {
+        return Ok(None);
+    }
+    if !dataset.dcpl()?.external().is_empty() {
+        return Ok(None);
+    }
+    let offset = hdf5::h5lock!(h5d::H5Dget_offset(dataset.id()));
+    // Not allocated yet, so only fill values.
+    if offset == h5::HADDR_UNDEF {
+        return Ok(None);
+    }
+    let file_dtype = dataset.dtype()?;
+    let mem_dtype = datatype::memory_type(dtype, &file_dtype)?;
+    if hdf5::h5call!(h5t::H5Tequal(file_dtype.id(), mem_dtype.id()))? <= 0 {
+        return Ok(None);
+    }
+    let len = dataset.size() * dtype.size();
+    if len == 0 {
+        return Ok(None);
+    }
+    let file = File::open(path)?;
+    if file.metadata()?.len() < offset + len as u64 {
+        return Err(format!(
+            "'{}' of '{path}' extends past the end of the file",
+            dataset.name()
+        )
+        .into());
+    }
+    Ok(Some(Mapping::new(&file, offset, len)?))
+}
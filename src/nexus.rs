@@ -0,0 +1,225 @@
+use super::{
+    datatype, mmap::Bytes, open_dataset, open_path, Hdf5FileData, Hdf5ReadBindData,
+    Hdf5ReadInitData, Hdf5ReadOptions, ReadRawBytes,
+};
+use duckdb::{
+    core::{DataChunkHandle, LogicalTypeHandle, LogicalTypeId},
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use hdf5::types::{CompoundField, CompoundType, TypeDescriptor, VarLenAscii, VarLenUnicode};
+use std::error::Error;
+
+/// The strings of the attribute `name` of `group`, scalar or
+/// one-dimensional, or `None` if it has no such attribute.
+pub(super) fn read_strings(
+    group: &hdf5::Location,
+    name: &str,
+) -> Result<Option<Vec<String>>, Box<dyn Error>> {
+    if !group.attr_names()?.iter().any(|n| n == name) {
+        return Ok(None);
+    }
+    let attr = group.attr(name)?;
+    let dtype = attr.dtype()?.to_descriptor()?;
+    let strings = match dtype {
+        TypeDescriptor::VarLenAscii => attr
+            .read_raw::<VarLenAscii>()?
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        TypeDescriptor::VarLenUnicode => attr
+            .read_raw::<VarLenUnicode>()?
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        TypeDescriptor::FixedAscii(_) | TypeDescriptor::FixedUnicode(_) => attr
+            .read_raw_bytes(&dtype)?
+            .chunks_exact(dtype.size())
+            .map(|item| {
+                let end = item.iter().position(|b| *b == 0).unwrap_or(item.len());
+                String::from_utf8_lossy(&item[..end]).trim_end().to_string()
+            })
+            .collect(),
+        _ => {
+            return Err(format!("attribute '{name}' of '{}' is not a string", group.name()).into())
+        }
+    };
+    Ok(Some(strings))
+}
+
+/// A column of the table of an NXdata group: an axis, whose items are
+/// repeated along the other dimensions, or the signal.
+struct Column {
+    name: String,
+    dtype: TypeDescriptor,
+    data: Vec<u8>,
+    /// The dimension of the signal an axis is along, `None` for the signal.
+    dim: Option<usize>,
+}
+
+/// The names of the axes of the signal of `group` and their dimensions, from
+/// its `axes` attribute, one per dimension, "." for those without one. As in
+/// older files, a single string may also list them separated by `:` or `,`,
+/// and an `AXISNAME_indices` attribute may give the dimension of an axis.
+fn nexus_axes(group: &hdf5::Group) -> Result<Vec<(String, usize)>, Box<dyn Error>> {
+    let mut names = read_strings(group, "axes")?.unwrap_or_default();
+    if let [name] = names.as_slice() {
+        names = name
+            .split([':', ','])
+            .map(|s| s.trim().to_string())
+            .collect();
+    }
+    let mut axes = vec![];
+    for (dim, name) in names.into_iter().enumerate() {
+        if name == "." || name.is_empty() {
+            continue;
+        }
+        let indices = format!("{name}_indices");
+        let dim = if group.attr_names()?.contains(&indices) {
+            let dims = group.attr(&indices)?.read_raw::<i64>()?;
+            match dims.first().map(|dim| usize::try_from(*dim)) {
+                Some(Ok(dim)) => dim,
+                _ => return Err(format!("invalid {indices} of '{}'", group.name()).into()),
+            }
+        } else {
+            dim
+        };
+        axes.push((name, dim));
+    }
+    Ok(axes)
+}
+
+/// Read the signal of the NXdata `group`, with a column of the coordinates of
+/// each of its axes before it.
+fn read_nexus(path: &str, group: &str) -> Result<Hdf5FileData, Box<dyn Error>> {
+    let file = open_path(path)?;
+    let nxdata = file.group(group)?;
+    if read_strings(&nxdata, "NX_class")?.as_deref() != Some(&["NXdata".to_string()]) {
+        return Err(format!("'{group}' of '{path}' is not an NXdata group").into());
+    }
+    let signal = read_strings(&nxdata, "signal")?
+        .and_then(|names| names.into_iter().next())
+        .ok_or_else(|| format!("NXdata group '{group}' of '{path}' has no signal attribute"))?;
+    let member = |name: &str| format!("{}/{name}", group.trim_end_matches('/'));
+    let dataset = open_dataset(&file, &member(&signal), path)?;
+    let shape = dataset.shape();
+    let mut columns = vec![];
+    for (name, dim) in nexus_axes(&nxdata)? {
+        if dim >= shape.len() {
+            return Err(format!(
+                "axis '{name}' of '{group}' of '{path}' is along dimension {dim}, \
+                 but its signal has {} dimensions",
+                shape.len()
+            )
+            .into());
+        }
+        let axis = open_dataset(&file, &member(&name), path)?;
+        if axis.ndim() != 1 || axis.size() != shape[dim] {
+            return Err(format!(
+                "axis '{name}' of '{group}' of '{path}' has shape {:?}, \
+                 but dimension {dim} of its signal has {} items",
+                axis.shape(),
+                shape[dim]
+            )
+            .into());
+        }
+        let dtype = datatype::to_descriptor(&axis.dtype()?)?;
+        columns.push(Column {
+            name,
+            data: axis.read_raw_bytes(&dtype)?,
+            dtype,
+            dim: Some(dim),
+        });
+    }
+    let dtype = datatype::to_descriptor(&dataset.dtype()?)?;
+    columns.push(Column {
+        name: signal,
+        data: dataset.read_raw_bytes(&dtype)?,
+        dtype,
+        dim: None,
+    });
+
+    let mut fields: Vec<CompoundField> = vec![];
+    let mut size = 0;
+    for c in &columns {
+        let field = CompoundField::new(&c.name, c.dtype.clone(), size, fields.len());
+        size += field.ty.size();
+        fields.push(field);
+    }
+    let len = dataset.size();
+    // The number of items of the signal between consecutive items along
+    // each dimension.
+    let strides = (0..shape.len())
+        .map(|dim| shape[dim + 1..].iter().product::<usize>())
+        .collect::<Vec<_>>();
+    let mut data = vec![0; len * size];
+    for (c, f) in columns.iter().zip(&fields) {
+        let item_size = c.dtype.size();
+        for (index, record) in data.chunks_exact_mut(size).enumerate() {
+            let item = match c.dim {
+                Some(dim) => (index / strides[dim]) % shape[dim],
+                None => index,
+            };
+            record[f.offset..][..item_size]
+                .copy_from_slice(&c.data[item * item_size..][..item_size]);
+        }
+    }
+    let dtype = TypeDescriptor::Compound(CompoundType { fields, size });
+    Ok(Hdf5FileData {
+        data: Bytes::read(&dtype, data).into(),
+        dtype,
+        pending: None,
+        len,
+        stream: None,
+        scale: None,
+        attrs: vec![],
+        nulls: vec![],
+        _items: vec![],
+    })
+}
+
+/// `read_nexus(path, group)`: the signal of a NeXus NXdata group, with a
+/// column per axis.
+pub(super) struct Hdf5ReadNexus;
+
+impl VTab for Hdf5ReadNexus {
+    type InitData = Hdf5ReadInitData;
+    type BindData = Hdf5ReadBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let group = bind.get_parameter(1).to_string();
+        let options = Hdf5ReadOptions::from_bind(bind)?;
+        let data = Hdf5ReadBindData::from_data(read_nexus(&path, &group)?, &options)?;
+        for (name, dtype) in data.iter_dtype() {
+            bind.add_result_column(&name, dtype);
+        }
+        bind.set_cardinality(data.len() as _, true);
+        Ok(data)
+    }
+
+    fn init(init: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        let bind_data = unsafe { init.get_bind_data::<Self::BindData>().as_ref() }.unwrap();
+        Ok(bind_data.project(&init.get_column_indices()))
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        bind_data.scan(init_data, output)?;
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+
+    fn supports_pushdown() -> bool {
+        true
+    }
+}
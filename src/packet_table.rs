@@ -0,0 +1,70 @@
+use super::{open_dataset, open_path, Hdf5ReadBindData, Hdf5ReadInitData, Hdf5ReadOptions};
+use duckdb::{
+    core::{DataChunkHandle, LogicalTypeHandle, LogicalTypeId},
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use std::error::Error;
+
+/// Check that a dataset has the layout of a packet table: one-dimensional,
+/// chunked and unlimited, as created by `H5PTcreate`.
+fn check_packet_table(path: &str, dataset: &str) -> Result<(), Box<dyn Error>> {
+    let file = open_path(path)?;
+    let dataset = open_dataset(&file, dataset, path)?;
+    let reason = if dataset.ndim() != 1 {
+        "it is not one-dimensional"
+    } else if !dataset.is_chunked() {
+        "it is not chunked"
+    } else if !dataset.is_resizable() {
+        "it is not unlimited"
+    } else {
+        return Ok(());
+    };
+    Err(format!("'{}' is not a packet table, as {reason}", dataset.name()).into())
+}
+
+/// `read_hdf5_packet_table(path, dataset)`: the records of a packet table.
+pub(super) struct Hdf5ReadPacketTable;
+
+impl VTab for Hdf5ReadPacketTable {
+    type InitData = Hdf5ReadInitData;
+    type BindData = Hdf5ReadBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let dataset = bind.get_parameter(1).to_string();
+        check_packet_table(&path, &dataset)?;
+        let options = Hdf5ReadOptions::from_bind(bind)?;
+        let data = Hdf5ReadBindData::new(&path, Some(&dataset), &options)?;
+        for (name, dtype) in data.iter_dtype() {
+            bind.add_result_column(&name, dtype);
+        }
+        bind.set_cardinality(data.len() as _, true);
+        Ok(data)
+    }
+
+    fn init(init: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        let bind_data = unsafe { init.get_bind_data::<Self::BindData>().as_ref() }.unwrap();
+        Ok(bind_data.project(&init.get_column_indices()))
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        bind_data.scan(init_data, output)?;
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+
+    fn supports_pushdown() -> bool {
+        true
+    }
+}
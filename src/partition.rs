@@ -0,0 +1,97 @@
+use duckdb::{
+    core::{FlatVector, Inserter, LogicalTypeHandle, LogicalTypeId},
+    ffi,
+};
+use std::error::Error;
+
+/// Expand a glob pattern into the sorted list of matching files.
+/// Paths without wildcards are returned as-is.
+pub fn expand_path(path: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    if !path.contains(['*', '?', '[']) {
+        return Ok(vec![path.to_string()]);
+    }
+    let mut files = vec![];
+    for entry in glob::glob(path)? {
+        files.push(entry?.to_string_lossy().into_owned());
+    }
+    if files.is_empty() {
+        return Err(format!("no files found matching '{path}'").into());
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The `key=value` directories of a path, in order.
+fn hive_pairs(path: &str) -> Vec<(&str, &str)> {
+    let mut components = path.split(['/', '\\']).collect::<Vec<_>>();
+    // The file name itself is not a partition.
+    components.pop();
+    components
+        .into_iter()
+        .filter_map(|c| c.split_once('='))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+/// Whether a path is laid out as Hive-style partitions.
+pub fn is_hive_partitioned(path: &str) -> bool {
+    !hive_pairs(path).is_empty()
+}
+
+/// A Hive-style partition column, with one value per file.
+pub struct Partition {
+    pub name: String,
+    values: Vec<String>,
+    integer: bool,
+}
+
+impl Partition {
+    /// Parse the partition columns of the files, which must all share the same keys.
+    pub fn parse(files: &[String]) -> Result<Vec<Self>, Box<dyn Error>> {
+        let Some(first) = files.first() else {
+            return Ok(vec![]);
+        };
+        let mut partitions = hive_pairs(first)
+            .into_iter()
+            .map(|(key, _)| Self {
+                name: key.to_string(),
+                values: vec![],
+                integer: true,
+            })
+            .collect::<Vec<_>>();
+        for file in files {
+            let pairs = hive_pairs(file);
+            let keys = pairs.iter().map(|(key, _)| *key);
+            if !keys.eq(partitions.iter().map(|p| p.name.as_str())) {
+                return Err(
+                    format!("hive partition mismatch between '{first}' and '{file}'").into(),
+                );
+            }
+            for (partition, (_, value)) in partitions.iter_mut().zip(pairs) {
+                partition.integer &= value.parse::<i64>().is_ok();
+                partition.values.push(value.to_string());
+            }
+        }
+        Ok(partitions)
+    }
+
+    /// Values that are all integers are typed as `BIGINT`, otherwise `VARCHAR`.
+    pub fn logical_type(&self) -> LogicalTypeHandle {
+        if self.integer {
+            LogicalTypeId::Bigint.into()
+        } else {
+            LogicalTypeId::Varchar.into()
+        }
+    }
+
+    /// Write the value of the partition for `file` into `vector` at `row`.
+    pub fn fill(&self, file: usize, vector: ffi::duckdb_vector, row: usize) {
+        let value = self.values[file].as_str();
+        let mut vector = FlatVector::from(vector);
+        if self.integer {
+            vector.as_mut_slice::<i64>()[row] = value.parse().unwrap();
+        } else {
+            vector.insert(row, value);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use super::{
+    datatype, mmap::Bytes, open_path, Hdf5FileData, Hdf5ReadBindData, Hdf5ReadInitData,
+    Hdf5ReadOptions, ReadRawBytes,
+};
+use duckdb::{
+    core::{DataChunkHandle, LogicalTypeHandle, LogicalTypeId},
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use hdf5::{
+    types::{CompoundField, CompoundType, TypeDescriptor},
+    LocationType,
+};
+use std::error::Error;
+
+/// The items of a dataset of a record group, at `offset` in each record.
+struct Member {
+    dataset: hdf5::Dataset,
+    offset: usize,
+    dtype: TypeDescriptor,
+    data: Vec<u8>,
+}
+
+/// The compound type of the records of `group`, with a field per dataset and
+/// a nested compound per subgroup, in the order of their names. The items of
+/// the datasets are read into `members`, for records at `offset`.
+fn record_type(
+    group: &hdf5::Group,
+    path: &str,
+    offset: usize,
+    members: &mut Vec<Member>,
+) -> Result<TypeDescriptor, Box<dyn Error>> {
+    let mut fields: Vec<CompoundField> = vec![];
+    let mut size = 0;
+    for name in group.member_names()? {
+        let ty = match group.loc_type_by_name(&name)? {
+            LocationType::Group => record_type(&group.group(&name)?, path, offset + size, members)?,
+            LocationType::Dataset => {
+                let dataset = group.dataset(&name)?;
+                if dataset.ndim() != 1 {
+                    return Err(format!(
+                        "read_hdf5_record requires one-dimensional datasets, \
+                         but '{}' of '{path}' has {} dimensions",
+                        dataset.name(),
+                        dataset.ndim()
+                    )
+                    .into());
+                }
+                let dtype = datatype::to_descriptor(&dataset.dtype()?)?;
+                if dtype.size() == 0 {
+                    return Err(format!(
+                        "'{}' of '{path}' has a zero-size datatype",
+                        dataset.name()
+                    )
+                    .into());
+                }
+                members.push(Member {
+                    data: dataset.read_raw_bytes(&dtype)?,
+                    dataset,
+                    offset: offset + size,
+                    dtype: dtype.clone(),
+                });
+                dtype
+            }
+            // Named datatypes are no fields.
+            _ => continue,
+        };
+        let field = CompoundField::new(&name, ty, size, fields.len());
+        size += field.ty.size();
+        fields.push(field);
+    }
+    if fields.is_empty() {
+        return Err(format!("'{}' of '{path}' has no datasets", group.name()).into());
+    }
+    Ok(TypeDescriptor::Compound(CompoundType { fields, size }))
+}
+
+/// Read the datasets of `group`, of the same length, as records of a
+/// compound type, the inverse of flattening a nested compound.
+fn read_records(path: &str, group: &str) -> Result<Hdf5FileData, Box<dyn Error>> {
+    let file = open_path(path)?;
+    let mut members = vec![];
+    let dtype = record_type(&file.group(group)?, path, 0, &mut members)?;
+    let len = members[0].dataset.size();
+    for m in &members[1..] {
+        if m.dataset.size() != len {
+            return Err(format!(
+                "'{}' of '{path}' has {} items, but '{}' has {len}",
+                m.dataset.name(),
+                m.dataset.size(),
+                members[0].dataset.name()
+            )
+            .into());
+        }
+    }
+    let size = dtype.size();
+    let mut data = vec![0; len * size];
+    for m in &members {
+        let item_size = m.dtype.size();
+        for (record, item) in data
+            .chunks_exact_mut(size)
+            .zip(m.data.chunks_exact(item_size))
+        {
+            record[m.offset..][..item_size].copy_from_slice(item);
+        }
+    }
+    Ok(Hdf5FileData {
+        data: Bytes::read(&dtype, data).into(),
+        dtype,
+        pending: None,
+        len,
+        stream: None,
+        scale: None,
+        attrs: vec![],
+        nulls: vec![],
+        _items: vec![],
+    })
+}
+
+/// `read_hdf5_record(path, group)`: a row per index of the datasets of a
+/// group, with a `STRUCT` column per subgroup.
+pub(super) struct Hdf5ReadRecord;
+
+impl VTab for Hdf5ReadRecord {
+    type InitData = Hdf5ReadInitData;
+    type BindData = Hdf5ReadBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let group = bind.get_parameter(1).to_string();
+        let options = Hdf5ReadOptions::from_bind(bind)?;
+        let data = Hdf5ReadBindData::from_data(read_records(&path, &group)?, &options)?;
+        for (name, dtype) in data.iter_dtype() {
+            bind.add_result_column(&name, dtype);
+        }
+        bind.set_cardinality(data.len() as _, true);
+        Ok(data)
+    }
+
+    fn init(init: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        let bind_data = unsafe { init.get_bind_data::<Self::BindData>().as_ref() }.unwrap();
+        Ok(bind_data.project(&init.get_column_indices()))
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let bind_data = func.get_bind_data();
+        let init_data = func.get_init_data();
+        bind_data.scan(init_data, output)?;
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+
+    fn supports_pushdown() -> bool {
+        true
+    }
+}
@@ -0,0 +1,482 @@
+use super::{
+    dataset_len, datatype,
+    hyperslab::{read_selected, Selection},
+    widen_to_double, Hdf5ReadError,
+};
+use hdf5::types::{CompoundField, CompoundType, Reference, TypeDescriptor};
+use hdf5_sys::{
+    h5d,
+    h5i::{self, hid_t, H5I_type_t},
+    h5o, h5p,
+    h5r::{self, H5R_ref_t, H5R_type_t},
+    h5s,
+    h5t::{self, H5T_class_t},
+};
+use std::error::Error;
+
+/// Items larger than this in total are never read with `deref`.
+const MAX_TARGET_SIZE: usize = 1 << 30;
+
+/// The kind of reference that `reference_type` reports for the unified
+/// references of HDF5 1.12, which may point to objects, regions or
+/// attributes: `H5Rget_type` tells each of them.
+pub const STD_REFERENCE: H5R_type_t = H5R_type_t::H5R_OBJECT2;
+
+/// The kind of reference stored in `dtype`, if it is an object, a region or
+/// a unified reference.
+pub fn reference_type(dtype: hid_t) -> Option<H5R_type_t> {
+    let is = |other: hid_t| hdf5::h5lock!(h5t::H5Tequal(dtype, other)) > 0;
+    if is(*h5t::H5T_STD_REF_OBJ) {
+        Some(H5R_type_t::H5R_OBJECT1)
+    } else if is(*h5t::H5T_STD_REF_DSETREG) {
+        Some(H5R_type_t::H5R_DATASET_REGION1)
+    } else if is(*h5t::H5T_STD_REF) {
+        Some(STD_REFERENCE)
+    } else {
+        None
+    }
+}
+
+/// The memory datatype of references of `ref_type`.
+pub fn reference_dtype(ref_type: H5R_type_t) -> hid_t {
+    match ref_type {
+        H5R_type_t::H5R_OBJECT1 => *h5t::H5T_STD_REF_OBJ,
+        H5R_type_t::H5R_DATASET_REGION1 => *h5t::H5T_STD_REF_DSETREG,
+        _ => *h5t::H5T_STD_REF,
+    }
+}
+
+/// The size of a reference of `ref_type` in memory.
+pub fn reference_size(ref_type: H5R_type_t) -> usize {
+    hdf5::h5lock!(h5t::H5Tget_size(reference_dtype(ref_type)))
+}
+
+/// Whether `dtype` is an object reference, as created by `H5Rcreate`, or a
+/// unified reference, as created by `H5Rcreate_object`.
+pub fn is_object_reference(dtype: &hdf5::Datatype) -> bool {
+    matches!(
+        reference_type(dtype.id()),
+        Some(H5R_type_t::H5R_OBJECT1 | STD_REFERENCE)
+    )
+}
+
+/// Whether a unified `reference` points to an object of `kind`.
+pub fn is_std_reference_to(reference: &[u8], kind: H5R_type_t) -> bool {
+    hdf5::h5lock!(h5r::H5Rget_type(reference.as_ptr().cast())) == kind
+}
+
+/// The path of the object that `reference`, stored in the file of `loc`,
+/// points to.
+pub fn reference_path(loc: hid_t, ref_type: H5R_type_t, reference: &[u8]) -> Option<String> {
+    let get_name = |name: *mut u8, size: usize| {
+        hdf5::h5lock!(if ref_type == STD_REFERENCE {
+            h5r::H5Rget_obj_name(
+                reference.as_ptr().cast(),
+                h5p::H5P_DEFAULT,
+                name.cast(),
+                size,
+            )
+        } else {
+            h5r::H5Rget_name(loc, ref_type, reference.as_ptr().cast(), name.cast(), size)
+        })
+    };
+    // Null references have no name.
+    let len = usize::try_from(get_name(std::ptr::null_mut(), 0))
+        .ok()
+        .filter(|len| *len > 0)?;
+    let mut name = vec![0u8; len + 1];
+    get_name(name.as_mut_ptr(), name.len());
+    name.truncate(len);
+    String::from_utf8(name).ok()
+}
+
+/// Open the object that `reference`, stored in the file of `loc`, points to.
+pub fn open_reference(loc: hid_t, ref_type: H5R_type_t, reference: &[u8]) -> hdf5::Result<hid_t> {
+    if ref_type == STD_REFERENCE {
+        hdf5::h5call!(h5r::H5Ropen_object(
+            reference.as_ptr().cast(),
+            h5p::H5P_DEFAULT,
+            h5p::H5P_DEFAULT
+        ))
+    } else {
+        hdf5::h5call!(h5r::H5Rdereference2(
+            loc,
+            h5p::H5P_DEFAULT,
+            ref_type,
+            reference.as_ptr().cast()
+        ))
+    }
+}
+
+/// The references of `ref_type` read from a file. Unified references hold
+/// their file and buffers of their own in memory, and are destroyed on drop.
+pub struct StoredReferences {
+    ref_type: H5R_type_t,
+    len: usize,
+    /// Words, for the alignment of `H5R_ref_t`.
+    buffer: Vec<u64>,
+}
+
+impl StoredReferences {
+    /// Read the items of `dataset` as `dtype`, holding references of
+    /// `ref_type`.
+    pub fn read(dataset: &hdf5::Dataset, dtype: hid_t, ref_type: H5R_type_t) -> hdf5::Result<Self> {
+        Self::read_selection(dataset, dtype, ref_type, None)
+    }
+
+    /// Read the items of `dataset` in `selection`, or all of them, as
+    /// `dtype`, holding references of `ref_type`.
+    pub fn read_selection(
+        dataset: &hdf5::Dataset,
+        dtype: hid_t,
+        ref_type: H5R_type_t,
+        selection: Option<&Selection>,
+    ) -> hdf5::Result<Self> {
+        let items = match selection {
+            Some(selection) => selection.len(),
+            None => dataset_len(dataset)?,
+        };
+        let len = items * hdf5::h5lock!(h5t::H5Tget_size(dtype));
+        let mut stored = Self {
+            ref_type,
+            len: 0,
+            buffer: vec![0; len.div_ceil(8)],
+        };
+        if len == 0 {
+            return Ok(stored);
+        }
+        let buffer = stored.buffer.as_mut_ptr().cast();
+        match selection {
+            Some(selection) => {
+                let file_space = selection.select(dataset)?;
+                let dims = [items as u64];
+                let mem_space: hdf5::Dataspace = unsafe {
+                    hdf5::from_id(hdf5::h5call!(h5s::H5Screate_simple(
+                        1,
+                        dims.as_ptr(),
+                        std::ptr::null()
+                    ))?)?
+                };
+                hdf5::h5call!(h5d::H5Dread(
+                    dataset.id(),
+                    dtype,
+                    mem_space.id(),
+                    file_space.id(),
+                    h5p::H5P_DEFAULT,
+                    buffer
+                ))?;
+            }
+            None => {
+                hdf5::h5call!(h5d::H5Dread(
+                    dataset.id(),
+                    dtype,
+                    h5s::H5S_ALL,
+                    h5s::H5S_ALL,
+                    h5p::H5P_DEFAULT,
+                    buffer
+                ))?;
+            }
+        }
+        stored.len = len;
+        Ok(stored)
+    }
+
+    /// Read the items of `attr` as `dtype`, holding references of `ref_type`.
+    pub fn read_attribute(
+        attr: &hdf5::Attribute,
+        dtype: &hdf5::Datatype,
+        ref_type: H5R_type_t,
+    ) -> hdf5::Result<Self> {
+        let len = attr.size() * dtype.size();
+        let mut stored = Self {
+            ref_type,
+            len: 0,
+            buffer: vec![0; len.div_ceil(8)],
+        };
+        hdf5::h5call!(hdf5_sys::h5a::H5Aread(
+            attr.id(),
+            dtype.id(),
+            stored.buffer.as_mut_ptr().cast()
+        ))?;
+        stored.len = len;
+        Ok(stored)
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.buffer.as_ptr().cast(), self.len) }
+    }
+
+    /// The references, one by one.
+    pub fn items(&self) -> std::slice::ChunksExact<'_, u8> {
+        self.bytes().chunks_exact(reference_size(self.ref_type))
+    }
+}
+
+impl Drop for StoredReferences {
+    fn drop(&mut self) {
+        if self.ref_type != STD_REFERENCE {
+            return;
+        }
+        let count = self.len / std::mem::size_of::<H5R_ref_t>();
+        let refs = self.buffer.as_mut_ptr().cast::<H5R_ref_t>();
+        for index in 0..count {
+            hdf5::h5lock!(h5r::H5Rdestroy(refs.add(index)));
+        }
+    }
+}
+
+/// The element type of `file_dtype` and the kind of its references, if it is
+/// a fixed array of references.
+pub fn reference_array_type(
+    file_dtype: &hdf5::Datatype,
+) -> hdf5::Result<Option<(hdf5::Datatype, H5R_type_t)>> {
+    if hdf5::h5lock!(h5t::H5Tget_class(file_dtype.id())) != H5T_class_t::H5T_ARRAY {
+        return Ok(None);
+    }
+    let base: hdf5::Datatype =
+        unsafe { hdf5::from_id(hdf5::h5call!(h5t::H5Tget_super(file_dtype.id()))?)? };
+    Ok(reference_type(base.id()).map(|ref_type| (base, ref_type)))
+}
+
+/// A dataset of fixed arrays of references, read as arrays of the paths they
+/// point to.
+pub struct References {
+    /// The type of the rows, a fixed array of strings.
+    pub dtype: TypeDescriptor,
+    /// The rows, as string pointers into `items`, null for null references.
+    pub data: Vec<u8>,
+    pub items: Vec<Vec<u8>>,
+}
+
+impl References {
+    /// Read the items of `dataset` in `selection`, or all of them, if its
+    /// datatype is a fixed array of references.
+    pub fn read(
+        dataset: &hdf5::Dataset,
+        file_dtype: &hdf5::Datatype,
+        selection: Option<&Selection>,
+    ) -> hdf5::Result<Option<Self>> {
+        let Some((base, ref_type)) = reference_array_type(file_dtype)? else {
+            return Ok(None);
+        };
+        // References have no conversion, so they are read as stored.
+        let stored =
+            StoredReferences::read_selection(dataset, file_dtype.id(), ref_type, selection)?;
+        let len = file_dtype.size() / base.size();
+        let mut data = Vec::with_capacity(stored.items().len() * std::mem::size_of::<*const u8>());
+        let mut items = vec![];
+        for reference in stored.items() {
+            let Some(path) = reference_path(dataset.id(), ref_type, reference) else {
+                data.extend_from_slice(&0usize.to_ne_bytes());
+                continue;
+            };
+            let mut path = path.into_bytes();
+            path.push(0);
+            data.extend_from_slice(&(path.as_ptr() as usize).to_ne_bytes());
+            // Moving the buffer keeps its bytes in place.
+            items.push(path);
+        }
+        Ok(Some(Self {
+            dtype: TypeDescriptor::FixedArray(Box::new(TypeDescriptor::VarLenUnicode), len),
+            data,
+            items,
+        }))
+    }
+}
+
+/// The datasets referenced by a dataset of object references, read as one
+/// item per row: the value of a scalar dataset, or the first item of others.
+pub struct Targets {
+    /// The type of the rows, the type of the referenced datasets.
+    pub dtype: TypeDescriptor,
+    pub data: Vec<u8>,
+}
+
+impl Targets {
+    /// Read the datasets referenced by `dataset` of a file named `name` in
+    /// errors, following one level of references: every reference must
+    /// point to a dataset of the same type, which isn't a reference.
+    pub fn read(
+        dataset: &hdf5::Dataset,
+        name: &str,
+        all_double: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        let ref_type = reference_type(dataset.dtype()?.id()).unwrap_or(H5R_type_t::H5R_OBJECT1);
+        let refs = StoredReferences::read(dataset, reference_dtype(ref_type), ref_type)?;
+        let mut item_dtype = None;
+        let mut data = vec![];
+        for (row, reference) in refs.items().enumerate() {
+            if ref_type == STD_REFERENCE && !is_std_reference_to(reference, H5R_type_t::H5R_OBJECT2)
+            {
+                return Err(format!(
+                    "reference at row {row} of '{}' of '{name}' is not an object reference",
+                    dataset.name()
+                )
+                .into());
+            }
+            let Some(path) = reference_path(dataset.id(), ref_type, reference) else {
+                return Err(format!(
+                    "reference at row {row} of '{}' of '{name}' is null",
+                    dataset.name()
+                )
+                .into());
+            };
+            let id = open_reference(dataset.id(), ref_type, reference)?;
+            if hdf5::h5lock!(h5i::H5Iget_type(id)) != H5I_type_t::H5I_DATASET {
+                hdf5::h5lock!(h5o::H5Oclose(id));
+                return Err(format!(
+                    "'{path}' referenced by '{}' of '{name}' is not a dataset",
+                    dataset.name()
+                )
+                .into());
+            }
+            let target: hdf5::Dataset = unsafe { hdf5::from_id(id)? };
+            let target_dtype = target.dtype()?;
+            if hdf5::h5lock!(h5t::H5Tget_class(target_dtype.id())) == H5T_class_t::H5T_REFERENCE {
+                return Err(format!(
+                    "deref follows one level of references, but '{path}' referenced by '{}' \
+                     of '{name}' holds references",
+                    dataset.name()
+                )
+                .into());
+            }
+            let mut dtype = datatype::to_descriptor(&target_dtype)?;
+            if all_double {
+                dtype = widen_to_double(&dtype);
+            }
+            match &item_dtype {
+                Some(item_dtype) if *item_dtype != dtype => {
+                    return Err(format!(
+                        "references of '{}' of '{name}' point to datasets of different types",
+                        dataset.name()
+                    )
+                    .into())
+                }
+                Some(_) => {}
+                None => item_dtype = Some(dtype.clone()),
+            }
+            if data.len() + dtype.size() > MAX_TARGET_SIZE {
+                return Err(Hdf5ReadError::TooLarge(format!(
+                    "targets of '{}' of '{name}' are too large to read, as they have more than {MAX_TARGET_SIZE} bytes",
+                    dataset.name()
+                ))
+                .into());
+            }
+            // Select the first item, the only one of a scalar dataset.
+            let space = target.space()?;
+            if dataset_len(&target)? == 0 {
+                return Err(format!(
+                    "'{path}' referenced by '{}' of '{name}' has no items",
+                    dataset.name()
+                )
+                .into());
+            }
+            if !target.is_scalar() {
+                let (start, count) = (vec![0; space.ndim()], vec![1; space.ndim()]);
+                hdf5::h5call!(h5s::H5Sselect_hyperslab(
+                    space.id(),
+                    h5s::H5S_SELECT_SET,
+                    start.as_ptr(),
+                    std::ptr::null(),
+                    count.as_ptr(),
+                    std::ptr::null()
+                ))?;
+            }
+            data.extend(read_selected(&target, &space, 1, &dtype)?);
+        }
+        let dtype = item_dtype
+            .ok_or_else(|| format!("'{}' of '{name}' has no references", dataset.name()))?;
+        Ok(Self { dtype, data })
+    }
+}
+
+/// The kind of an object or region reference of `dtype`. Unified references
+/// hold buffers of their own, so only these are resolved within compounds.
+fn field_reference_type(dtype: &TypeDescriptor) -> Option<H5R_type_t> {
+    match dtype {
+        TypeDescriptor::Reference(Reference::Object) => Some(H5R_type_t::H5R_OBJECT1),
+        TypeDescriptor::Reference(Reference::Region) => Some(H5R_type_t::H5R_DATASET_REGION1),
+        _ => None,
+    }
+}
+
+/// Whether `dtype` is a compound with fields of object or region references,
+/// directly or in nested compounds.
+pub fn has_reference_fields(dtype: &TypeDescriptor) -> bool {
+    match dtype {
+        TypeDescriptor::Compound(c) => c
+            .fields
+            .iter()
+            .any(|f| field_reference_type(&f.ty).is_some() || has_reference_fields(&f.ty)),
+        _ => false,
+    }
+}
+
+/// The items of a compound with fields of references, read as the paths they
+/// point to.
+pub struct ReferenceFields {
+    /// The type of the items, with strings for the references.
+    pub dtype: TypeDescriptor,
+    /// The items, with string pointers into `items` for the references, null
+    /// for null references.
+    pub data: Vec<u8>,
+    pub items: Vec<Vec<u8>>,
+}
+
+impl ReferenceFields {
+    /// Resolve the references of `data`, items of the compound `dtype` read
+    /// from the file of `loc`. A string pointer fits in the place of any
+    /// reference, so the other fields keep their offsets.
+    pub fn resolve(loc: hid_t, dtype: &TypeDescriptor, data: &[u8]) -> Self {
+        // The type of `dtype` at `offset` in an item, with strings for its
+        // references, whose offsets and kinds are added to `refs`.
+        fn resolved(
+            dtype: &TypeDescriptor,
+            offset: usize,
+            refs: &mut Vec<(usize, H5R_type_t)>,
+        ) -> TypeDescriptor {
+            if let Some(ref_type) = field_reference_type(dtype) {
+                refs.push((offset, ref_type));
+                return TypeDescriptor::VarLenUnicode;
+            }
+            match dtype {
+                TypeDescriptor::Compound(c) => TypeDescriptor::Compound(CompoundType {
+                    fields: c
+                        .fields
+                        .iter()
+                        .map(|f| CompoundField {
+                            ty: resolved(&f.ty, offset + f.offset, refs),
+                            ..f.clone()
+                        })
+                        .collect(),
+                    size: c.size,
+                }),
+                dtype => dtype.clone(),
+            }
+        }
+        let mut refs = vec![];
+        let resolved_dtype = resolved(dtype, 0, &mut refs);
+        let mut data = data.to_vec();
+        let mut items = vec![];
+        for item in data.chunks_exact_mut(dtype.size()) {
+            for (offset, ref_type) in &refs {
+                let path = reference_path(loc, *ref_type, &item[*offset..]);
+                let field = &mut item[*offset..][..std::mem::size_of::<usize>()];
+                // Null references become null pointers, read as NULL.
+                let Some(path) = path else {
+                    field.fill(0);
+                    continue;
+                };
+                let mut path = path.into_bytes();
+                path.push(0);
+                field.copy_from_slice(&(path.as_ptr() as usize).to_ne_bytes());
+                items.push(path);
+            }
+        }
+        Self {
+            dtype: resolved_dtype,
+            data,
+            items,
+        }
+    }
+}
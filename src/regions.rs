@@ -0,0 +1,130 @@
+use super::{
+    dataset_len, datatype,
+    hyperslab::read_selected,
+    references::{
+        is_std_reference_to, open_reference, reference_dtype, reference_type, StoredReferences,
+        STD_REFERENCE,
+    },
+    widen_to_double, Hdf5ReadError,
+};
+use hdf5::types::TypeDescriptor;
+use hdf5_sys::{
+    h5i::hid_t,
+    h5p,
+    h5r::{self, H5R_type_t},
+    h5s,
+    h5t::hvl_t,
+};
+use std::error::Error;
+
+/// Region data larger than this is never read with `deref_regions`.
+const MAX_REGION_SIZE: usize = 1 << 30;
+
+/// Whether `dtype` is a region reference, as created by `H5Rcreate`, or a
+/// unified reference, as created by `H5Rcreate_region`.
+pub fn is_region_reference(dtype: &hdf5::Datatype) -> bool {
+    matches!(
+        reference_type(dtype.id()),
+        Some(H5R_type_t::H5R_DATASET_REGION1 | STD_REFERENCE)
+    )
+}
+
+/// The selection of the region that `reference`, stored in the file of
+/// `loc`, points to.
+fn reference_region(loc: hid_t, ref_type: H5R_type_t, reference: &[u8]) -> hdf5::Result<hid_t> {
+    if ref_type == STD_REFERENCE {
+        hdf5::h5call!(h5r::H5Ropen_region(
+            reference.as_ptr().cast(),
+            h5p::H5P_DEFAULT,
+            h5p::H5P_DEFAULT
+        ))
+    } else {
+        hdf5::h5call!(h5r::H5Rget_region(loc, ref_type, reference.as_ptr().cast()))
+    }
+}
+
+/// The regions referenced by a dataset, read as a list of items per row.
+pub struct Regions {
+    /// The type of the rows, a variable-length array of the referenced items.
+    pub dtype: TypeDescriptor,
+    /// The rows, as `hvl_t` pointing into `items`.
+    pub data: Vec<u8>,
+    pub items: Vec<Vec<u8>>,
+}
+
+impl Regions {
+    /// Read the regions referenced by `dataset` of a file named `name` in
+    /// errors. Every region must belong to a dataset of the same type.
+    pub fn read(
+        dataset: &hdf5::Dataset,
+        name: &str,
+        all_double: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        let len = dataset_len(dataset)?;
+        let ref_type =
+            reference_type(dataset.dtype()?.id()).unwrap_or(H5R_type_t::H5R_DATASET_REGION1);
+        let refs = StoredReferences::read(dataset, reference_dtype(ref_type), ref_type)?;
+        let mut item_dtype = None;
+        let mut data = Vec::with_capacity(len * std::mem::size_of::<hvl_t>());
+        let mut items = vec![];
+        let mut size = 0;
+        for (row, r) in refs.items().enumerate() {
+            if ref_type == STD_REFERENCE && !is_std_reference_to(r, H5R_type_t::H5R_DATASET_REGION2)
+            {
+                return Err(format!(
+                    "reference at row {row} of '{}' of '{name}' is not a region reference",
+                    dataset.name()
+                )
+                .into());
+            }
+            let target: hdf5::Dataset =
+                unsafe { hdf5::from_id(open_reference(dataset.id(), ref_type, r)?)? };
+            let space: hdf5::Dataspace =
+                unsafe { hdf5::from_id(reference_region(dataset.id(), ref_type, r)?)? };
+            let mut dtype = datatype::to_descriptor(&target.dtype()?)?;
+            if all_double {
+                dtype = widen_to_double(&dtype);
+            }
+            match &item_dtype {
+                Some(item_dtype) if *item_dtype != dtype => {
+                    return Err(format!(
+                        "regions of '{}' of '{name}' belong to datasets of different types",
+                        dataset.name()
+                    )
+                    .into())
+                }
+                Some(_) => {}
+                None => item_dtype = Some(dtype.clone()),
+            }
+            let count = hdf5::h5call!(h5s::H5Sget_select_npoints(space.id()))? as usize;
+            size += count * dtype.size();
+            if size > MAX_REGION_SIZE {
+                return Err(Hdf5ReadError::TooLarge(format!(
+                    "regions of '{}' of '{name}' are too large to read, as they have more than {MAX_REGION_SIZE} bytes",
+                    dataset.name()
+                ))
+                .into());
+            }
+            let mut buffer = read_selected(&target, &space, count, &dtype)?;
+            let row = hvl_t {
+                len: count,
+                p: buffer.as_mut_ptr().cast(),
+            };
+            data.extend_from_slice(unsafe {
+                std::slice::from_raw_parts(
+                    (&row as *const hvl_t).cast::<u8>(),
+                    std::mem::size_of::<hvl_t>(),
+                )
+            });
+            // Moving the buffer keeps its items in place.
+            items.push(buffer);
+        }
+        let item_dtype =
+            item_dtype.ok_or_else(|| format!("'{}' of '{name}' has no regions", dataset.name()))?;
+        Ok(Self {
+            dtype: TypeDescriptor::VarLenArray(Box::new(item_dtype)),
+            data,
+            items,
+        })
+    }
+}
@@ -0,0 +1,285 @@
+use super::{
+    dataset_len, datatype, datatype_name, open_dataset, open_path, stream::has_vlen, Hdf5ReadError,
+    ReadRawBytes,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use duckdb::{
+    core::{DataChunkHandle, FlatVector, Inserter, LogicalTypeHandle, LogicalTypeId},
+    ffi::duckdb_string_t,
+    types::DuckString,
+    vscalar::{ScalarFunctionSignature, VScalar},
+    vtab::arrow::WritableVector,
+};
+use hdf5_sys::h5l::{self, H5L_type_t};
+use std::{error::Error, ffi::CStr};
+
+/// Read the VARCHAR at `row` of a flat vector.
+fn get_string(vector: &FlatVector, row: usize) -> String {
+    let mut s = vector.as_slice_with_len::<duckdb_string_t>(row + 1)[row];
+    DuckString::new(&mut s).as_str().into_owned()
+}
+
+/// `hdf5_dtype(path, dataset)`: the datatype of a dataset as a readable string.
+pub struct Hdf5Dtype;
+
+impl Hdf5Dtype {
+    fn dtype(path: &str, dataset: &str) -> hdf5::Result<String> {
+        let file = open_path(path)?;
+        Ok(datatype_name(&open_dataset(&file, dataset, path)?.dtype()?))
+    }
+}
+
+impl VScalar for Hdf5Dtype {
+    type State = ();
+
+    unsafe fn invoke(
+        _: &Self::State,
+        input: &mut DataChunkHandle,
+        output: &mut dyn WritableVector,
+    ) -> Result<(), Box<dyn Error>> {
+        let paths = input.flat_vector(0);
+        let datasets = input.flat_vector(1);
+        let mut output = output.flat_vector();
+        for row in 0..input.len() {
+            if paths.row_is_null(row as _) || datasets.row_is_null(row as _) {
+                output.set_null(row);
+                continue;
+            }
+            let path = get_string(&paths, row);
+            let dataset = get_string(&datasets, row);
+            output.insert(row, Self::dtype(&path, &dataset)?.as_str());
+        }
+        Ok(())
+    }
+
+    fn signatures() -> Vec<ScalarFunctionSignature> {
+        vec![ScalarFunctionSignature::exact(
+            vec![LogicalTypeId::Varchar.into(), LogicalTypeId::Varchar.into()],
+            LogicalTypeId::Varchar.into(),
+        )]
+    }
+}
+
+/// The largest dataset encoded by `hdf5_read_base64`, in bytes.
+const MAX_BASE64_SIZE: usize = 1 << 24;
+
+/// `hdf5_read_base64(path, dataset)`: the items of a dataset in native byte
+/// order, encoded as base64.
+pub struct Hdf5ReadBase64;
+
+impl Hdf5ReadBase64 {
+    fn encode(path: &str, dataset: &str) -> Result<String, Box<dyn Error>> {
+        let file = open_path(path)?;
+        let dataset = open_dataset(&file, dataset, path)?;
+        let dtype = datatype::to_descriptor(&dataset.dtype()?)?;
+        // The bytes of variable-length items would be pointers.
+        if has_vlen(&dtype) {
+            return Err(format!(
+                "hdf5_read_base64 requires fixed-size items, but '{}' of '{path}' is {}",
+                dataset.name(),
+                datatype_name(&dataset.dtype()?)
+            )
+            .into());
+        }
+        let size = dataset_len(&dataset)? * dtype.size();
+        if size > MAX_BASE64_SIZE {
+            return Err(Hdf5ReadError::TooLarge(format!(
+                "'{}' of '{path}' is too large to encode as base64, as it has {size} bytes, more than {MAX_BASE64_SIZE}",
+                dataset.name()
+            ))
+            .into());
+        }
+        Ok(STANDARD.encode(dataset.read_raw_bytes(&dtype)?))
+    }
+}
+
+impl VScalar for Hdf5ReadBase64 {
+    type State = ();
+
+    unsafe fn invoke(
+        _: &Self::State,
+        input: &mut DataChunkHandle,
+        output: &mut dyn WritableVector,
+    ) -> Result<(), Box<dyn Error>> {
+        let paths = input.flat_vector(0);
+        let datasets = input.flat_vector(1);
+        let mut output = output.flat_vector();
+        for row in 0..input.len() {
+            if paths.row_is_null(row as _) || datasets.row_is_null(row as _) {
+                output.set_null(row);
+                continue;
+            }
+            let path = get_string(&paths, row);
+            let dataset = get_string(&datasets, row);
+            output.insert(row, Self::encode(&path, &dataset)?.as_str());
+        }
+        Ok(())
+    }
+
+    fn signatures() -> Vec<ScalarFunctionSignature> {
+        vec![ScalarFunctionSignature::exact(
+            vec![LogicalTypeId::Varchar.into(), LogicalTypeId::Varchar.into()],
+            LogicalTypeId::Varchar.into(),
+        )]
+    }
+}
+
+/// The target of a link: the path of a soft link, with the file for an
+/// external link.
+struct LinkTarget {
+    file: Option<String>,
+    path: String,
+}
+
+/// `hdf5_readlink(path, link_path)`: the target of a soft or external link,
+/// without following it, or NULL for a hard link.
+pub struct Hdf5Readlink;
+
+impl Hdf5Readlink {
+    fn readlink(path: &str, link: &str) -> Result<Option<LinkTarget>, Box<dyn Error>> {
+        let file = open_path(path)?;
+        let name = std::ffi::CString::new(link)?;
+        let mut info = h5l::H5L_info2_t::default();
+        hdf5::h5call!(h5l::H5Lget_info2(
+            file.id(),
+            name.as_ptr(),
+            &mut info,
+            hdf5_sys::h5p::H5P_DEFAULT
+        ))?;
+        if info.type_ == H5L_type_t::H5L_TYPE_HARD {
+            return Ok(None);
+        }
+        let size = unsafe { *info.u.val_size() };
+        let mut value = vec![0u8; size];
+        hdf5::h5call!(h5l::H5Lget_val(
+            file.id(),
+            name.as_ptr(),
+            value.as_mut_ptr().cast(),
+            size,
+            hdf5_sys::h5p::H5P_DEFAULT
+        ))?;
+        match info.type_ {
+            H5L_type_t::H5L_TYPE_SOFT => Ok(Some(LinkTarget {
+                file: None,
+                path: CStr::from_bytes_until_nul(&value)?
+                    .to_string_lossy()
+                    .into_owned(),
+            })),
+            H5L_type_t::H5L_TYPE_EXTERNAL => {
+                let mut flags = 0;
+                let mut file_name = std::ptr::null();
+                let mut obj_path = std::ptr::null();
+                hdf5::h5call!(h5l::H5Lunpack_elink_val(
+                    value.as_ptr().cast(),
+                    size,
+                    &mut flags,
+                    &mut file_name,
+                    &mut obj_path
+                ))?;
+                // Both point into `value`.
+                let (file_name, obj_path) =
+                    unsafe { (CStr::from_ptr(file_name), CStr::from_ptr(obj_path)) };
+                Ok(Some(LinkTarget {
+                    file: Some(file_name.to_string_lossy().into_owned()),
+                    path: obj_path.to_string_lossy().into_owned(),
+                }))
+            }
+            _ => Err(format!("'{link}' is a user-defined link").into()),
+        }
+    }
+}
+
+impl VScalar for Hdf5Readlink {
+    type State = ();
+
+    unsafe fn invoke(
+        _: &Self::State,
+        input: &mut DataChunkHandle,
+        output: &mut dyn WritableVector,
+    ) -> Result<(), Box<dyn Error>> {
+        let paths = input.flat_vector(0);
+        let links = input.flat_vector(1);
+        let mut output = output.struct_vector();
+        let mut files = output.child(0, input.len());
+        let mut targets = output.child(1, input.len());
+        for row in 0..input.len() {
+            let target = if paths.row_is_null(row as _) || links.row_is_null(row as _) {
+                None
+            } else {
+                Self::readlink(&get_string(&paths, row), &get_string(&links, row))?
+            };
+            match target {
+                Some(LinkTarget { file, path }) => {
+                    match file {
+                        Some(file) => files.insert(row, file.as_str()),
+                        None => files.set_null(row),
+                    }
+                    targets.insert(row, path.as_str());
+                }
+                None => {
+                    // The fields of a NULL struct must be NULL too.
+                    files.set_null(row);
+                    targets.set_null(row);
+                    output.set_null(row);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn signatures() -> Vec<ScalarFunctionSignature> {
+        vec![ScalarFunctionSignature::exact(
+            vec![LogicalTypeId::Varchar.into(), LogicalTypeId::Varchar.into()],
+            LogicalTypeHandle::struct_type(&[
+                ("file", LogicalTypeId::Varchar.into()),
+                ("path", LogicalTypeId::Varchar.into()),
+            ]),
+        )]
+    }
+}
+
+/// `hdf5_version()`: the versions of the extension and of the linked HDF5
+/// library, with the features that depend on how HDF5 was built.
+pub struct Hdf5Version;
+
+impl VScalar for Hdf5Version {
+    type State = ();
+
+    unsafe fn invoke(
+        _: &Self::State,
+        input: &mut DataChunkHandle,
+        output: &mut dyn WritableVector,
+    ) -> Result<(), Box<dyn Error>> {
+        let (major, minor, release) = hdf5::library_version();
+        let hdf5 = format!("{major}.{minor}.{release}");
+        let threadsafe = hdf5::is_library_threadsafe();
+        // SWMR reads are part of every HDF5 since 1.10.
+        let swmr = (major, minor) >= (1, 10);
+        let direct = super::driver::direct_supported();
+        let output = output.struct_vector();
+        let mut columns = (0..5)
+            .map(|i| output.child(i, input.len()))
+            .collect::<Vec<_>>();
+        for row in 0..input.len() {
+            columns[0].insert(row, env!("CARGO_PKG_VERSION"));
+            columns[1].insert(row, hdf5.as_str());
+            columns[2].as_mut_slice::<bool>()[row] = threadsafe;
+            columns[3].as_mut_slice::<bool>()[row] = swmr;
+            columns[4].as_mut_slice::<bool>()[row] = direct;
+        }
+        Ok(())
+    }
+
+    fn signatures() -> Vec<ScalarFunctionSignature> {
+        vec![ScalarFunctionSignature::exact(
+            vec![],
+            LogicalTypeHandle::struct_type(&[
+                ("extension", LogicalTypeId::Varchar.into()),
+                ("hdf5", LogicalTypeId::Varchar.into()),
+                ("threadsafe", LogicalTypeId::Boolean.into()),
+                ("swmr", LogicalTypeId::Boolean.into()),
+                ("direct", LogicalTypeId::Boolean.into()),
+            ]),
+        )]
+    }
+}
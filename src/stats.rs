@@ -0,0 +1,190 @@
+use super::{
+    check_szip, dataset_len, datatype, dtype_name, hyperslab::Selection, open_dataset, open_path,
+    read_f64, ReadRawBytes, RESULT_COLNAME,
+};
+use duckdb::{
+    core::{DataChunkHandle, Inserter, LogicalTypeHandle, LogicalTypeId},
+    ffi,
+    vtab::{BindInfo, InitInfo, TableFunctionInfo, VTab},
+};
+use hdf5::types::TypeDescriptor;
+use std::{
+    error::Error,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The items of a dataset are read in blocks of about this many bytes.
+const BLOCK_SIZE: usize = 1 << 24;
+
+/// The statistics of a numeric column, accumulated item by item.
+struct ColumnStats {
+    name: String,
+    /// The offset of the column in an item, and its type.
+    offset: usize,
+    dtype: TypeDescriptor,
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: u64,
+    /// The number of NaN values, left out of the other statistics.
+    null_count: u64,
+}
+
+impl ColumnStats {
+    fn new(name: &str, offset: usize, dtype: &TypeDescriptor) -> Self {
+        Self {
+            name: name.to_string(),
+            offset,
+            dtype: dtype.clone(),
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+            count: 0,
+            null_count: 0,
+        }
+    }
+
+    fn add(&mut self, item: &[u8]) {
+        let value = read_f64(&self.dtype, &item[self.offset..]).unwrap();
+        if value.is_nan() {
+            self.null_count += 1;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+            self.sum += value;
+            self.count += 1;
+        }
+    }
+}
+
+fn is_numeric(dtype: &TypeDescriptor) -> bool {
+    matches!(
+        dtype,
+        TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) | TypeDescriptor::Float(_)
+    )
+}
+
+/// Accumulate the statistics of the numeric columns of `dataset` in one pass
+/// over blocks of its outer axis.
+fn column_stats(path: &str, dataset: &hdf5::Dataset) -> Result<Vec<ColumnStats>, Box<dyn Error>> {
+    check_szip(dataset, path)?;
+    let file_dtype = dataset.dtype()?;
+    let dtype = datatype::to_descriptor(&file_dtype)?;
+    let mut columns: Vec<ColumnStats> = match &dtype {
+        TypeDescriptor::Compound(c) => c
+            .fields
+            .iter()
+            .filter(|f| is_numeric(&f.ty))
+            .map(|f| ColumnStats::new(&f.name, f.offset, &f.ty))
+            .collect(),
+        dtype if is_numeric(dtype) => vec![ColumnStats::new(&RESULT_COLNAME, 0, dtype)],
+        _ => vec![],
+    };
+    if columns.is_empty() {
+        return Err(format!(
+            "hdf5_column_stats requires numeric columns, but '{}' of '{path}' is {}",
+            dataset.name(),
+            dtype_name(&dtype)
+        )
+        .into());
+    }
+    let shape = dataset.shape();
+    let mut add = |data: &[u8]| {
+        for item in data.chunks_exact(dtype.size()) {
+            for column in &mut columns {
+                column.add(item);
+            }
+        }
+    };
+    if shape.is_empty() || dataset_len(dataset)? == 0 {
+        add(&dataset.read_raw_bytes(&dtype)?);
+    } else {
+        let row_size = shape[1..].iter().product::<usize>() * dtype.size();
+        let block = (BLOCK_SIZE / row_size.max(1)).max(1);
+        for start in (0..shape[0]).step_by(block) {
+            let mut selection = Selection {
+                offset: vec![0; shape.len()],
+                count: shape.iter().map(|n| *n as u64).collect(),
+                stride: vec![1; shape.len()],
+            };
+            selection.offset[0] = start as u64;
+            selection.count[0] = block.min(shape[0] - start) as u64;
+            add(&selection.read(dataset, &dtype)?);
+        }
+    }
+    Ok(columns)
+}
+
+pub struct Hdf5ColumnStatsBindData {
+    columns: Vec<ColumnStats>,
+}
+
+pub struct Hdf5ColumnStatsInitData {
+    /// The index of the next vector to emit.
+    chunk: AtomicUsize,
+}
+
+/// `hdf5_column_stats(path, dataset)`: the minimum, maximum, mean and counts
+/// of each numeric column of a dataset.
+pub(super) struct Hdf5ColumnStats;
+
+impl VTab for Hdf5ColumnStats {
+    type InitData = Hdf5ColumnStatsInitData;
+    type BindData = Hdf5ColumnStatsBindData;
+
+    fn bind(bind: &BindInfo) -> Result<Self::BindData, Box<dyn Error>> {
+        let path = bind.get_parameter(0).to_string();
+        let dataset = bind.get_parameter(1).to_string();
+        let file = open_path(&path)?;
+        let columns = column_stats(&path, &open_dataset(&file, &dataset, &path)?)?;
+        bind.add_result_column("column_name", LogicalTypeId::Varchar.into());
+        bind.add_result_column("min", LogicalTypeId::Double.into());
+        bind.add_result_column("max", LogicalTypeId::Double.into());
+        bind.add_result_column("mean", LogicalTypeId::Double.into());
+        bind.add_result_column("count", LogicalTypeId::UBigint.into());
+        bind.add_result_column("null_count", LogicalTypeId::UBigint.into());
+        bind.set_cardinality(columns.len() as _, true);
+        Ok(Hdf5ColumnStatsBindData { columns })
+    }
+
+    fn init(_: &InitInfo) -> Result<Self::InitData, Box<dyn Error>> {
+        Ok(Hdf5ColumnStatsInitData {
+            chunk: AtomicUsize::new(0),
+        })
+    }
+
+    fn func(
+        func: &TableFunctionInfo<Self>,
+        output: &mut DataChunkHandle,
+    ) -> Result<(), Box<dyn Error>> {
+        let columns = &func.get_bind_data().columns;
+        let capacity = unsafe { ffi::duckdb_vector_size() } as usize;
+        let chunk = func.get_init_data().chunk.fetch_add(1, Ordering::Relaxed);
+        let start = (chunk * capacity).min(columns.len());
+        let count = (columns.len() - start).min(capacity);
+        for (row, c) in columns[start..start + count].iter().enumerate() {
+            output.flat_vector(0).insert(row, c.name.as_str());
+            // Columns without values have no minimum, maximum or mean.
+            let values = [c.min, c.max, c.sum / c.count as f64];
+            for (col, value) in values.into_iter().enumerate() {
+                let mut vector = output.flat_vector(col + 1);
+                if c.count == 0 {
+                    vector.set_null(row);
+                } else {
+                    vector.as_mut_slice::<f64>()[row] = value;
+                }
+            }
+            output.flat_vector(4).as_mut_slice::<u64>()[row] = c.count;
+            output.flat_vector(5).as_mut_slice::<u64>()[row] = c.null_count;
+        }
+        output.set_len(count);
+        Ok(())
+    }
+
+    fn parameters() -> Option<Vec<LogicalTypeHandle>> {
+        Some(vec![
+            LogicalTypeId::Varchar.into(),
+            LogicalTypeId::Varchar.into(),
+        ])
+    }
+}
@@ -0,0 +1,243 @@
+use super::{datatype, hyperslab::read_range, swap_bytes};
+use hdf5::types::TypeDescriptor;
+use hdf5_sys::{h5p, h5s, h5t};
+use std::{
+    ops::Deref,
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender},
+        Mutex,
+    },
+    thread::JoinHandle,
+};
+
+/// Whether items of `dtype` point to variable-length data.
+pub fn has_vlen(dtype: &TypeDescriptor) -> bool {
+    match dtype {
+        TypeDescriptor::VarLenArray(_)
+        | TypeDescriptor::VarLenAscii
+        | TypeDescriptor::VarLenUnicode => true,
+        TypeDescriptor::Compound(c) => c.fields.iter().any(|f| has_vlen(&f.ty)),
+        TypeDescriptor::FixedArray(ty, _) => has_vlen(ty),
+        _ => false,
+    }
+}
+
+/// A dataset of variable-length items, read a vector at a time during the
+/// scan instead of whole in bind. HDF5 allocates the payloads of every item
+/// read, so this keeps the payloads of only one vector in memory.
+pub struct VlenStream {
+    pub dataset: hdf5::Dataset,
+    /// The type the items are read as, before flattening.
+    pub dtype: TypeDescriptor,
+    /// Whether to reverse the bytes of numeric elements.
+    pub swap: bool,
+    /// Whether to read the next batch in the background while the current
+    /// one is emitted.
+    pub prefetch: bool,
+    /// Whether the batches are read from the last to the first, so the one
+    /// prefetched is before the current one.
+    pub reverse: bool,
+    /// The thread reading the batches in the background, started by the
+    /// first prefetch.
+    pub worker: Mutex<Option<Prefetch>>,
+}
+
+/// A thread reading a batch at a time in the background, at most one ahead.
+pub struct Prefetch {
+    requests: SyncSender<(usize, usize)>,
+    results: Receiver<hdf5::Result<VlenBatch>>,
+    handle: JoinHandle<()>,
+    /// The items `start..start + len` requested and not yet received.
+    pending: Option<(usize, usize)>,
+}
+
+impl Prefetch {
+    fn spawn(dataset: hdf5::Dataset, dtype: TypeDescriptor, swap: bool) -> Self {
+        let (requests, jobs) = sync_channel::<(usize, usize)>(1);
+        let (done, results) = sync_channel(1);
+        let handle = std::thread::spawn(move || {
+            for (start, len) in jobs {
+                if done
+                    .send(read_batch(&dataset, &dtype, swap, start, len))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+        Self {
+            requests,
+            results,
+            handle,
+            pending: None,
+        }
+    }
+
+    /// Wait for the batch requested last, if any, and return it if it holds
+    /// the items `start..start + len`.
+    fn take(&mut self, start: usize, len: usize) -> hdf5::Result<Option<VlenBatch>> {
+        let Some(pending) = self.pending.take() else {
+            return Ok(None);
+        };
+        let batch = self
+            .results
+            .recv()
+            .map_err(|_| hdf5::Error::from("the prefetch thread stopped unexpectedly"))?;
+        match pending == (start, len) {
+            true => batch.map(Some),
+            // A batch that isn't the next one is reclaimed unused.
+            false => Ok(None),
+        }
+    }
+
+    fn request(&mut self, start: usize, len: usize) -> hdf5::Result<()> {
+        self.requests
+            .send((start, len))
+            .map_err(|_| hdf5::Error::from("the prefetch thread stopped unexpectedly"))?;
+        self.pending = Some((start, len));
+        Ok(())
+    }
+}
+
+impl VlenStream {
+    /// Read the `len` items from the item `start` on, then prefetch as many
+    /// items after them, or before them if reversed.
+    pub fn read(&self, start: usize, len: usize) -> hdf5::Result<VlenBatch> {
+        let mut worker = self
+            .worker
+            .lock()
+            .map_err(|_| hdf5::Error::from("the prefetch thread panicked"))?;
+        let prefetched = match worker.as_mut() {
+            Some(w) => w.take(start, len)?,
+            None => None,
+        };
+        let batch = match prefetched {
+            Some(batch) => batch,
+            None => read_batch(&self.dataset, &self.dtype, self.swap, start, len)?,
+        };
+        let (next, len) = match self.reverse {
+            true => (start.saturating_sub(len), len.min(start)),
+            false => (
+                start + len,
+                len.min(self.dataset.size().saturating_sub(start + len)),
+            ),
+        };
+        if self.prefetch && len > 0 {
+            worker
+                .get_or_insert_with(|| {
+                    Prefetch::spawn(self.dataset.clone(), self.dtype.clone(), self.swap)
+                })
+                .request(next, len)?;
+        }
+        Ok(batch)
+    }
+}
+
+impl Drop for VlenStream {
+    fn drop(&mut self) {
+        // Stop the thread once it ends a prefetch of a query that ended
+        // early; the batch left in the channel is reclaimed with it.
+        if let Some(Prefetch {
+            requests,
+            results,
+            handle,
+            ..
+        }) = self.worker.get_mut().ok().and_then(Option::take)
+        {
+            drop(requests);
+            drop(handle.join());
+            drop(results);
+        }
+    }
+}
+
+fn read_batch(
+    dataset: &hdf5::Dataset,
+    dtype: &TypeDescriptor,
+    swap: bool,
+    start: usize,
+    len: usize,
+) -> hdf5::Result<VlenBatch> {
+    let mut data = read_range(dataset, start, len, dtype)?;
+    if swap {
+        for item in data.chunks_exact_mut(dtype.size()) {
+            swap_bytes(dtype, item);
+        }
+    }
+    Ok(VlenBatch {
+        dtype: dtype.clone(),
+        start,
+        len,
+        data,
+    })
+}
+
+/// Items read from a stream, whose payloads are reclaimed on drop.
+pub struct VlenBatch {
+    dtype: TypeDescriptor,
+    /// The index of the first item in the dataset.
+    pub start: usize,
+    pub len: usize,
+    pub data: Vec<u8>,
+}
+
+impl Drop for VlenBatch {
+    fn drop(&mut self) {
+        // Failing to reclaim only leaks the payloads.
+        let _ = reclaim(&self.dtype, self.len, &mut self.data);
+    }
+}
+
+/// Items read whole by HDF5, outside of a stream, whose payloads are
+/// reclaimed on drop.
+pub struct VlenItems {
+    dtype: TypeDescriptor,
+    data: Vec<u8>,
+}
+
+impl VlenItems {
+    pub fn new(dtype: &TypeDescriptor, data: Vec<u8>) -> Self {
+        Self {
+            dtype: dtype.clone(),
+            data,
+        }
+    }
+}
+
+impl Deref for VlenItems {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Drop for VlenItems {
+    fn drop(&mut self) {
+        let len = self.data.len() / self.dtype.size();
+        let _ = reclaim(&self.dtype, len, &mut self.data);
+    }
+}
+
+/// Free the payloads HDF5 allocated for the `len` items of `dtype` in `data`.
+fn reclaim(dtype: &TypeDescriptor, len: usize, data: &mut [u8]) -> hdf5::Result<()> {
+    if len == 0 {
+        return Ok(());
+    }
+    let native_dtype = datatype::from_descriptor(dtype)?;
+    let dims = [len as u64];
+    let mem_space: hdf5::Dataspace = unsafe {
+        hdf5::from_id(hdf5::h5call!(h5s::H5Screate_simple(
+            1,
+            dims.as_ptr(),
+            std::ptr::null()
+        ))?)?
+    };
+    hdf5::h5call!(h5t::H5Treclaim(
+        native_dtype.id(),
+        mem_space.id(),
+        h5p::H5P_DEFAULT,
+        data.as_mut_ptr().cast()
+    ))?;
+    Ok(())
+}